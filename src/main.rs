@@ -5,17 +5,44 @@ mod game1 {
 }
 
 fn main() {
+    // `zk-battleship verify-transcript [file]` re-verifies a saved game instead of playing one
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("verify-transcript") {
+        let path = args.get(2).map(String::as_str).unwrap_or(game1::TRANSCRIPT_PATH);
+        game1::verify_transcript_file(path);
+        return;
+    }
+    // `zk-battleship referee [address]` referees a match between two clients instead of playing
+    if args.get(1).map(String::as_str) == Some("referee") {
+        let address = args.get(2).map(String::as_str).unwrap_or(game1::DEFAULT_ADDRESS);
+        game1::run_referee(address);
+        return;
+    }
+
+    // anything else is game options, e.g. `zk-battleship --grid 8 --fleet 4,3,2` or `--config game.json`
+    let config = match game1::GameConfig::from_args(&args[1..]) {
+        Ok(config) => config,
+        Err(e) => {
+            println!("{}", e);
+            println!("{}", game1::CONFIG_USAGE);
+            return;
+        }
+    };
+
     println!("Choose the mode for the Battleship game:");
     println!("1. SNARKs (Halo2/Bulletproofs)");
+    println!("2. Verify a saved game transcript (transcript.json)");
+    println!("3. Referee a match between two TCP clients");
 
     let mut choice = String::new();
     stdin().read_line(&mut choice).unwrap();
 
     match choice.trim() {
-        "1" => game1::run(),
+        "1" => game1::run(&config),
+        "2" => game1::verify_transcript_file(game1::TRANSCRIPT_PATH),
+        "3" => game1::run_referee(game1::DEFAULT_ADDRESS),
         _ => {
-            println!("Please choose 1.");
-            return;
+            println!("Please choose 1, 2 or 3.");
         }
     };
 }
@@ -33,11 +33,17 @@
 // bulletproofs = "5.0.0"
 // curve25519-dalek = "4.1.3"
 // merlin = "3.0.0"
+// serde = { version = "1.0", features = ["derive"] }
+// serde_json = "1.0"
+// base64 = "0.23"
+// blake2b_simd = "1.0"
 
 // dependencies
 use std::io::stdin;
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::collections::BTreeMap;
 
 use rand::Rng;
 use rand::rngs::OsRng;
@@ -45,7 +51,8 @@ use rand::RngCore;
 
 // iszero chip imports has some overlap with other halo2 imports fix below if needed
 use halo2_proofs::{circuit::*, plonk::*, poly::Rotation};
-use halo2curves::ff::Field;
+use halo2curves::ff::{Field, PrimeField};
+use halo2curves::group::{Curve, Group, GroupEncoding};
 
 use halo2_gadgets::poseidon::{
     primitives::{ConstantLength, P128Pow5T3, Hash as PoseidonPrimitiveHash},
@@ -58,199 +65,820 @@ use halo2_proofs::{
         create_proof, verify_proof, keygen_pk, keygen_vk, VerifyingKey, ProvingKey},
     poly::commitment::Params,
     transcript::{Blake2bWrite, Blake2bRead, Challenge255},
-    pasta::{Fp, EqAffine},
+    pasta::{Fp, Fq, EqAffine, pallas},
+    arithmetic::{CurveAffine, CurveExt},
 };
 
 use halo2_proofs::plonk::SingleVerifier;
 
 use bulletproofs::{BulletproofGens, PedersenGens, RangeProof};
-use curve25519_dalek::{scalar::Scalar, ristretto::CompressedRistretto};
+use curve25519_dalek::{scalar::Scalar, ristretto::{CompressedRistretto, RistrettoPoint}};
 use merlin::Transcript;
 
+use serde::{Deserialize, Serialize};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+
 #[derive(Clone, Debug)]
 struct ShipCommitment {
     commitment:  Fp,    
     salt: u64,          // random salt for blinding
 }
 
-struct BattleshipGame {
-    grid_size: usize,
-    grid: Vec<Vec<u8>>,
+// the public half of a ShipCommitment, the salt never leaves the prover
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct PublishedCommitment(Fp);
+
+// which way a ship extends from its origin cell
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Orientation {
+    Horizontal, // extends along +x
+    Vertical,   // extends along +y
+}
+
+impl Orientation {
+    // 0 for horizontal, 1 for vertical (this is what goes into the commitment)
+    fn as_u64(self) -> u64 {
+        match self {
+            Orientation::Horizontal => 0,
+            Orientation::Vertical => 1,
+        }
+    }
+}
+
+// a ship is a straight segment of `length` cells starting at (x, y)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Ship {
+    x: u8,
+    y: u8,
+    orientation: Orientation,
+    length: u8,
+}
+
+impl Ship {
+    // every (x, y) cell the ship covers, starting at the origin
+    fn cells(&self) -> Vec<(u8, u8)> {
+        (0..self.length)
+            .map(|k| match self.orientation {
+                Orientation::Horizontal => (self.x + k, self.y),
+                Orientation::Vertical => (self.x, self.y + k),
+            })
+            .collect()
+    }
+}
+
+// placement rule variants, some rule sets don't let ships touch each other
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum AdjacencyRule {
+    Allowed,   // ships may touch, they just can't overlap
+    Forbidden, // ships may not touch, not even diagonally
+}
+
+// classic Hasbro fleet (name, length), ships are placed and committed in this order
+const DEFAULT_FLEET: [(&str, u8); 5] = [
+    ("Carrier", 5),
+    ("Battleship", 4),
+    ("Cruiser", 3),
+    ("Submarine", 3),
+    ("Destroyer", 2),
+];
+
+// ship lengths of the default fleet, this is what fixes the circuit shape
+fn default_fleet_lengths() -> Vec<u8> {
+    DEFAULT_FLEET.iter().map(|&(_, length)| length).collect()
+}
+
+// name of the ship at `index`, custom fleets don't have the classic names so their ships just get numbered
+fn ship_name(fleet: &[u8], index: usize) -> String {
+    match DEFAULT_FLEET.get(index) {
+        Some(&(name, _)) if fleet == default_fleet_lengths() => name.to_string(),
+        _ => format!("Ship {}", index + 1),
+    }
+}
+
+// how a side commits to its board at setup
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum CommitmentMode {
+    PerShip, // one Poseidon commitment per ship, shot proofs loop over every ship
+    Board,   // one Merkle root over the whole grid, shot proofs open a single cell
+}
+
+// two-to-one Poseidon hash used for the board Merkle tree (leaves and inner nodes)
+fn poseidon_pair(left: Fp, right: Fp) -> Fp {
+    PoseidonPrimitiveHash::<Fp, P128Pow5T3, ConstantLength<2>, 3, 2>::init().hash([left, right])
+}
+
+// board dimensions, x runs along the width and y along the height
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GridSize {
+    width: usize,
+    height: usize,
+}
+
+impl GridSize {
+    fn cells(&self) -> usize {
+        self.width * self.height
+    }
+
+    fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height
+    }
+
+    // row major cell index, the same order the Merkle leaves are in
+    fn index(&self, x: usize, y: usize) -> usize {
+        y * self.width + x
+    }
+}
+
+impl std::fmt::Display for GridSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+// number of Merkle tree levels needed to cover every cell of the board
+fn board_depth(grid_size: GridSize) -> usize {
+    let cells = grid_size.cells();
+    let mut depth = 0;
+    while (1usize << depth) < cells {
+        depth += 1;
+    }
+    depth
+}
+
+// whole board commitment: Poseidon Merkle tree over salted cell occupancy bits
+// leaves are Poseidon(occupied, salt) in row major order (index = y * width + x)
+// and the tree is padded with Poseidon(0, 0) leaves up to a power of two
+// only the root is published, so the opponent doesn't even learn how many ships there are
+#[derive(Clone, Debug)]
+struct BoardCommitment {
+    root: Fp,
+    occupied: Vec<bool>,   // occupancy at commit time, one per cell
+    salts: Vec<u64>,       // random salt per cell
+    levels: Vec<Vec<Fp>>,  // levels[0] are the leaves, the last level is [root]
+}
+
+impl BoardCommitment {
+    fn new(grid_size: GridSize, grid: &[Vec<u8>]) -> Self {
+        let occupied: Vec<bool> = grid.iter().flat_map(|row| row.iter().map(|&cell| cell != 0)).collect();
+        let salts: Vec<u64> = occupied.iter().map(|_| OsRng.next_u64()).collect();
+        Self::from_openings(grid_size, occupied, salts)
+    }
+
+    // rebuilds the tree from the opened cells, this is also how a revealed board is checked
+    fn from_openings(grid_size: GridSize, occupied: Vec<bool>, salts: Vec<u64>) -> Self {
+        let mut leaves: Vec<Fp> = occupied
+            .iter()
+            .zip(salts.iter())
+            .map(|(&occupied, &salt)| poseidon_pair(Fp::from(occupied as u64), Fp::from(salt)))
+            .collect();
+        leaves.resize(1 << board_depth(grid_size), poseidon_pair(Fp::ZERO, Fp::ZERO));
+
+        let mut levels = vec![leaves];
+        while levels.last().unwrap().len() > 1 {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| poseidon_pair(pair[0], pair[1]))
+                .collect();
+            levels.push(next);
+        }
+
+        BoardCommitment {
+            root: levels.last().unwrap()[0],
+            occupied,
+            salts,
+            levels,
+        }
+    }
+
+    // sibling hashes from the leaf at index up to (not including) the root
+    fn path(&self, index: usize) -> Vec<Fp> {
+        self.levels[..self.levels.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(level, nodes)| nodes[(index >> level) ^ 1])
+            .collect()
+    }
+}
+
+// end of game opening of everything one side committed to
+#[derive(Clone, Debug)]
+struct BoardReveal {
+    ships: Vec<(Ship, u64)>,              // every ship with its salt, in commitment order
+    cells: Option<(Vec<bool>, Vec<u64>)>, // board mode only: occupancy bit and salt of every cell
+}
+
+// private side of a board: ship positions, salts and blinding factors,
+// everything needed to prove things about it. the opponent only ever gets a Verifier of it
+struct Prover {
+    grid_size: GridSize,
+    grid: Vec<Vec<u8>>, // one row per y
+    fleet: Vec<u8>, // lengths of the ships still to be / already placed, in order
+    adjacency: AdjacencyRule,
     ship_commitments: Vec<ShipCommitment>,
-    ship_positions: Vec<(u8, u8)>,
+    ships: Vec<Ship>,
     ship_range_proofs: Vec<ShipPlacementProof>,
+    fleet_range_proof: Option<RangeProof>, // one aggregated Bulletproof for every ship, made once the fleet is placed
+    range_blindings: Vec<(Scalar, Scalar)>, // private Ristretto blindings of each ship's x and y
+    bridge_blindings: Vec<(Fq, Fq)>, // private Pallas blindings of each ship's bridged x and y
+    board_commitment: Option<BoardCommitment>, // only set in board commitment mode
+    hits_taken: Vec<(u8, u8)>, // public history of confirmed hits on this board, in order
+    sunk_announced: Vec<bool>, // which ships have already been proven sunk
 }
 
+// a ship coordinate as published at placement, it is range proved by the aggregated fleet proof
 #[derive(Clone)]
-struct CoordinateProof {
+struct CoordinateCommitment {
     commitment: CompressedRistretto,
-    proof: RangeProof,
+    bridge: CoordinateBridge, // ties the range proved value to the Poseidon ship commitment
+}
+
+// commitment to size - 1 - v worked out from the commitment to v: (size - 1) * B - C, where size is
+// the width or height depending on the axis. the verifier computes this itself so the prover can't
+// swap in an unrelated value
+fn upper_bound_commitment(commitment: &CompressedRistretto, size: usize) -> Option<CompressedRistretto> {
+    let pc_gens = PedersenGens::default();
+    let commitment = commitment.decompress()?;
+    Some((Scalar::from((size - 1) as u64) * pc_gens.B - commitment).compress())
 }
 
 #[derive(Clone)]
 struct ShipPlacementProof {
-    x: CoordinateProof,
-    y: CoordinateProof,
+    x: CoordinateCommitment,
+    y: CoordinateCommitment,
+}
+
+// choose number of bits for Bulletproof range: smallest k with 2^k >= the longer side of the board
+// (the aggregated proof takes one bit size for every value, the upper bound commitments keep each axis tight)
+// Bulletproofs require bit sizes to be multiples of 8
+fn range_bits(grid_size: GridSize) -> usize {
+    let mut bits = 0usize;
+    let mut bound = 1usize;
+    while bound < grid_size.width.max(grid_size.height) {
+        bound <<= 1;
+        bits += 1;
+    }
+    // Round up to nearest multiple of 8
+    let bits = bits.max(1);
+    bits.div_ceil(8) * 8
+}
+
+// commitments the aggregated fleet range proof is over, in proving order:
+// x, width - 1 - x, y, height - 1 - y for every ship, then identity commitments (0 with
+// blinding 0) up to a power of two since Bulletproofs aggregation needs that many values.
+// the verifier builds this list itself from the published coordinate commitments
+fn fleet_range_commitments(placements: &[ShipPlacementProof], grid_size: GridSize) -> Option<Vec<CompressedRistretto>> {
+    let mut commitments = Vec::new();
+    for placement in placements {
+        for (coordinate, size) in [(&placement.x, grid_size.width), (&placement.y, grid_size.height)] {
+            commitments.push(coordinate.commitment);
+            commitments.push(upper_bound_commitment(&coordinate.commitment, size)?);
+        }
+    }
+    commitments.resize(commitments.len().next_power_of_two(), RistrettoPoint::identity().compress());
+    Some(commitments)
+}
+
+// the Bulletproofs commitments live on Ristretto while the SNARKs work over Pallas, so a coordinate
+// is linked across in two steps:
+//   1. every bit of it is committed on both curves with an OR proof that both hide 0 or both hide 1,
+//      and the bit commitments add up (weighted by 2^i) to the Ristretto commitment on one side and
+//      a Pallas Pedersen commitment on the other
+//   2. the link SNARK opens that Pallas commitment against the Poseidon ship commitment
+#[derive(Clone)]
+struct CoordinateBridge {
+    pallas_commitment: pallas::Point, // value * G + blinding * H on Pallas
+    bits: Vec<BitEqualityProof>,
+}
+
+// Chaum-Pedersen style OR proof (Cramer-Damgard-Schoenmakers) over two curves at once.
+// statement v in {0, 1}: c_r - v * G_r = r_r * H_r on Ristretto and c_p - v * G_p = r_p * H_p on Pallas.
+// the challenge is a 128 bit integer so the same number is a valid scalar on both curves
+#[derive(Clone)]
+struct BitEqualityProof {
+    ristretto_commitment: CompressedRistretto,
+    pallas_commitment: pallas::Point,
+    t_ristretto: [CompressedRistretto; 2],
+    t_pallas: [pallas::Point; 2],
+    e0: u128, // challenge of the 0 branch, the 1 branch gets e - e0 (mod 2^128)
+    z_ristretto: [Scalar; 2],
+    z_pallas: [Fq; 2],
+}
+
+// Pallas generators for the bridge (G, H and the offset the SNARK accumulator starts from),
+// hashed to the curve so nobody knows the discrete logs between them
+fn pallas_bridge_generators() -> (pallas::Point, pallas::Point, pallas::Point) {
+    let hasher = pallas::Point::hash_to_curve("zattleship-bridge");
+    (hasher(b"G"), hasher(b"H"), hasher(b"offset"))
+}
+
+// Fiat-Shamir challenge for one bit proof
+fn bit_challenge(
+    transcript: &mut Transcript,
+    ristretto_commitment: &CompressedRistretto,
+    pallas_commitment: &pallas::Point,
+    t_ristretto: &[CompressedRistretto; 2],
+    t_pallas: &[pallas::Point; 2],
+) -> u128 {
+    transcript.append_message(b"c_ristretto", ristretto_commitment.as_bytes());
+    transcript.append_message(b"c_pallas", &pallas_commitment.to_bytes());
+    for v in 0..2 {
+        transcript.append_message(b"t_ristretto", t_ristretto[v].as_bytes());
+        transcript.append_message(b"t_pallas", &t_pallas[v].to_bytes());
+    }
+    let mut buf = [0u8; 16];
+    transcript.challenge_bytes(b"e", &mut buf);
+    u128::from_le_bytes(buf)
+}
+
+impl BitEqualityProof {
+    fn prove(bit: bool, r_ristretto: Scalar, r_pallas: Fq, transcript: &mut Transcript) -> Self {
+        let pc_gens = PedersenGens::default();
+        let (g_p, h_p, _) = pallas_bridge_generators();
+
+        let c_r = pc_gens.commit(Scalar::from(bit as u64), r_ristretto);
+        let c_p = g_p * Fq::from(bit as u64) + h_p * r_pallas;
+        let p_r = [c_r, c_r - pc_gens.B];
+        let p_p = [c_p, c_p - g_p];
+
+        let real = bit as usize;
+        let fake = 1 - real;
+        let mut t_r = [RistrettoPoint::identity(); 2];
+        let mut t_p = [pallas::Point::identity(); 2];
+        let mut e = [0u128; 2];
+        let mut z_r = [Scalar::ZERO; 2];
+        let mut z_p = [Fq::ZERO; 2];
+
+        // simulate the branch that isn't true
+        e[fake] = OsRng.r#gen::<u128>();
+        z_r[fake] = Scalar::random(&mut OsRng);
+        z_p[fake] = Fq::random(OsRng);
+        t_r[fake] = z_r[fake] * pc_gens.B_blinding - Scalar::from(e[fake]) * p_r[fake];
+        t_p[fake] = h_p * z_p[fake] - p_p[fake] * Fq::from_u128(e[fake]);
+
+        // and prove the real one with whatever challenge is left over
+        let k_r = Scalar::random(&mut OsRng);
+        let k_p = Fq::random(OsRng);
+        t_r[real] = k_r * pc_gens.B_blinding;
+        t_p[real] = h_p * k_p;
+
+        let t_r = t_r.map(|t| t.compress());
+        let c_r = c_r.compress();
+        let e_total = bit_challenge(transcript, &c_r, &c_p, &t_r, &t_p);
+        e[real] = e_total.wrapping_sub(e[fake]);
+        z_r[real] = k_r + Scalar::from(e[real]) * r_ristretto;
+        z_p[real] = k_p + Fq::from_u128(e[real]) * r_pallas;
+
+        BitEqualityProof {
+            ristretto_commitment: c_r,
+            pallas_commitment: c_p,
+            t_ristretto: t_r,
+            t_pallas: t_p,
+            e0: e[0],
+            z_ristretto: z_r,
+            z_pallas: z_p,
+        }
+    }
+
+    fn verify(&self, transcript: &mut Transcript) -> bool {
+        let pc_gens = PedersenGens::default();
+        let (g_p, h_p, _) = pallas_bridge_generators();
+
+        let Some(c_r) = self.ristretto_commitment.decompress() else {
+            return false;
+        };
+        let p_r = [c_r, c_r - pc_gens.B];
+        let p_p = [self.pallas_commitment, self.pallas_commitment - g_p];
+
+        let e_total = bit_challenge(
+            transcript,
+            &self.ristretto_commitment,
+            &self.pallas_commitment,
+            &self.t_ristretto,
+            &self.t_pallas,
+        );
+        let e = [self.e0, e_total.wrapping_sub(self.e0)];
+
+        (0..2).all(|v| {
+            let Some(t_r) = self.t_ristretto[v].decompress() else {
+                return false;
+            };
+            self.z_ristretto[v] * pc_gens.B_blinding == t_r + Scalar::from(e[v]) * p_r[v]
+                && h_p * self.z_pallas[v] == self.t_pallas[v] + p_p[v] * Fq::from_u128(e[v])
+        })
+    }
+}
+
+impl CoordinateBridge {
+    // links `value` under the Ristretto commitment with blinding r_ristretto to a fresh Pallas commitment,
+    // returns the bridge and the Pallas blinding (the link SNARK needs it as a witness)
+    fn prove(
+        value: u8,
+        bits: usize,
+        r_ristretto: Scalar,
+        ristretto_commitment: &CompressedRistretto,
+    ) -> (Self, Fq) {
+        // split both blindings into per bit blindings that add back up with the same 2^i weights
+        let mut bit_r_ristretto: Vec<Scalar> = (0..bits).map(|_| Scalar::random(&mut OsRng)).collect();
+        let rest: Scalar = (1..bits).map(|i| Scalar::from(1u64 << i) * bit_r_ristretto[i]).sum();
+        bit_r_ristretto[0] = r_ristretto - rest;
+        let bit_r_pallas: Vec<Fq> = (0..bits).map(|_| Fq::random(OsRng)).collect();
+        let r_pallas: Fq = (0..bits).map(|i| Fq::from(1u64 << i) * bit_r_pallas[i]).sum();
+
+        let mut transcript = Transcript::new(b"battleship_bridge");
+        transcript.append_message(b"commitment", ristretto_commitment.as_bytes());
+        let bit_proofs: Vec<BitEqualityProof> = (0..bits)
+            .map(|i| {
+                let bit = (value >> i) & 1 == 1;
+                BitEqualityProof::prove(bit, bit_r_ristretto[i], bit_r_pallas[i], &mut transcript)
+            })
+            .collect();
+
+        let pallas_commitment = bit_proofs
+            .iter()
+            .enumerate()
+            .map(|(i, proof)| proof.pallas_commitment * Fq::from(1u64 << i))
+            .sum();
+
+        (CoordinateBridge { pallas_commitment, bits: bit_proofs }, r_pallas)
+    }
+
+    // checks the bit proofs and that the bits add up to both commitments
+    fn verify(&self, ristretto_commitment: &CompressedRistretto, bits: usize) -> bool {
+        if self.bits.len() != bits {
+            return false;
+        }
+        let Some(expected) = ristretto_commitment.decompress() else {
+            return false;
+        };
+
+        let mut transcript = Transcript::new(b"battleship_bridge");
+        transcript.append_message(b"commitment", ristretto_commitment.as_bytes());
+        if !self.bits.iter().all(|proof| proof.verify(&mut transcript)) {
+            return false;
+        }
+
+        let mut sum_ristretto = RistrettoPoint::identity();
+        let mut sum_pallas = pallas::Point::identity();
+        for (i, proof) in self.bits.iter().enumerate() {
+            let Some(c_r) = proof.ristretto_commitment.decompress() else {
+                return false;
+            };
+            sum_ristretto += Scalar::from(1u64 << i) * c_r;
+            sum_pallas += proof.pallas_commitment * Fq::from(1u64 << i);
+        }
+
+        sum_ristretto == expected && sum_pallas == self.pallas_commitment
+    }
 }
 
-impl BattleshipGame {
+impl Prover {
 
     // initializes the game and its start state 
-    fn new(size: usize) -> Self {
-        BattleshipGame {
-            grid_size: size, // e.g., 10 for a 10x10 grid
-            grid: vec![vec![0; size]; size], // 0: empty, 1: ship, 2: hit
+    fn new(grid_size: GridSize, fleet: &[u8], adjacency: AdjacencyRule) -> Self {
+        Prover {
+            grid_size, // e.g., 10x10 for the classic board
+            grid: vec![vec![0; grid_size.width]; grid_size.height], // 0: empty, 1: ship, 2: hit
+            fleet: fleet.to_vec(),          // ship lengths, e.g. 5/4/3/3/2 for the classic fleet
+            adjacency,                      // whether ships may touch each other
             ship_commitments: Vec::new(),   // stores commitments for ship positions
-            ship_positions: Vec::new(),     // stores ship (x, y) coordinates
-            ship_range_proofs: Vec::new(), // stores range proofs for ship placements
+            ships: Vec::new(),              // stores ship origin, orientation and length
+            ship_range_proofs: Vec::new(), // stores the range proved coordinate commitments for ship placements
+            fleet_range_proof: None,       // covers all of ship_range_proofs at once
+            range_blindings: Vec::new(),   // openings of the coordinate commitments
+            bridge_blindings: Vec::new(),  // openings of the Pallas side of the range proof bridges
+            board_commitment: None,        // Merkle commitment to the whole grid (board mode only)
+            hits_taken: Vec::new(),        // confirmed hits, both players know these
+            sunk_announced: vec![false; fleet.len()],
         }
     }
 
-    // number of ships still afloat
+    // number of ships still afloat (at least one cell not hit yet)
     fn ships_remaining(&self) -> usize {
-        self.grid
+        self.ships
             .iter()
-            .map(|row| row.iter().filter(|&&cell| cell == 1).count())
-            .sum()
+            .filter(|ship| {
+                ship.cells()
+                    .iter()
+                    .any(|&(x, y)| self.grid[y as usize][x as usize] == 1)
+            })
+            .count()
     }
 
-    // user facing debug output for commitments
-    fn print_commitments(&self, label: &str) {
-        println!("\n{} commitments (Poseidon digests):", label);
-        if self.ship_commitments.is_empty() {
-            println!("- none committed yet");
-            return;
-        }
-        for (idx, ShipCommitment { commitment, .. }) in self.ship_commitments.iter().enumerate() {
-            println!("- #{} => {:?}", idx + 1, commitment);
-        }
-        println!("(hashes are public; salts stay hidden to preserve secrecy)\n");
+    // length of the next ship to be placed, None once the fleet is complete
+    fn next_ship_length(&self) -> Option<u8> {
+        self.fleet.get(self.ships.len()).copied()
     }
 
-    // choose number of bits for Bulletproof range: smallest k with 2^k >= grid_size
-    // Bulletproofs require bit sizes to be multiples of 8
+    // bits the coordinates are range proved (and bridged) with
     fn bits_for_grid_size(&self) -> usize {
-        let mut bits = 0usize;
-        let mut bound = 1usize;
-        while bound < self.grid_size {
-            bound <<= 1;
-            bits += 1;
-        }
-        // Round up to nearest multiple of 8
-        let bits = bits.max(1);
-        ((bits + 7) / 8) * 8
+        range_bits(self.grid_size)
     }
 
-    // Generate and verify a range proof for a coordinate without revealing it
-    fn prove_coordinate_range(&self, coord: u8) -> Result<CoordinateProof, String> {
-        if coord as usize >= self.grid_size {
-            return Err(format!("Coordinate {} out of bounds; must be < {}", coord, self.grid_size));
+    // commits to a ship coordinate and bridges it over to Pallas, its range proof comes later with the
+    // rest of the fleet. `size` is the width for x and the height for y.
+    // returns the Ristretto and Pallas blindings alongside the commitment
+    fn commit_coordinate(&self, coord: u8, size: usize) -> Result<(CoordinateCommitment, Scalar, Fq), String> {
+        if coord as usize >= size {
+            return Err(format!("Coordinate {} out of bounds; must be < {}", coord, size));
         }
 
+        let pc_gens = PedersenGens::default();
+        let blinding = Scalar::random(&mut OsRng);
+        let commitment = pc_gens.commit(Scalar::from(coord as u64), blinding).compress();
+        let (bridge, pallas_blinding) =
+            CoordinateBridge::prove(coord, self.bits_for_grid_size(), blinding, &commitment);
+
+        Ok((CoordinateCommitment { commitment, bridge }, blinding, pallas_blinding))
+    }
+
+    // one aggregated range proof over every ship's coordinate commitments (see fleet_range_commitments)
+    // so the setup proof grows with log(fleet size) instead of linearly
+    fn prove_fleet_ranges(&mut self) -> Result<(), String> {
         let bits = self.bits_for_grid_size();
-        let bound = 1u64 << bits;
-        if (coord as u64) >= bound {
-            return Err(format!(
-                "Coordinate {} is not < 2^{} ({}); grid_size={}",
-                coord, bits, bound, self.grid_size
-            ));
+        let (width, height) = (self.grid_size.width, self.grid_size.height);
+
+        let mut values = Vec::new();
+        let mut blindings = Vec::new();
+        for (ship, &(x_blinding, y_blinding)) in self.ships.iter().zip(self.range_blindings.iter()) {
+            for (coord, size, blinding) in [(ship.x as u64, width, x_blinding), (ship.y as u64, height, y_blinding)] {
+                let top = (size - 1) as u64;
+                values.extend([coord, top - coord]);
+                blindings.extend([blinding, -blinding]);
+            }
         }
+        let parties = values.len().next_power_of_two();
+        values.resize(parties, 0);
+        blindings.resize(parties, Scalar::ZERO);
 
         let pc_gens = PedersenGens::default();
-        let bp_gens = BulletproofGens::new(bits, 1);
-        let blinding = Scalar::random(&mut OsRng);
-        let mut prover_transcript = Transcript::new(b"battleship_range_proof");
-
-        let (proof, commitment) = RangeProof::prove_single(
-            &bp_gens,
-            &pc_gens,
-            &mut prover_transcript,
-            coord as u64,
-            &blinding,
-            bits,
-        ).map_err(|e| format!(
-            "Failed to generate range proof (coord={}, bits={}, bound=2^{}={}): {:?}",
-            coord, bits, bits, bound, e
-        ))?;
-
-        let mut verifier_transcript = Transcript::new(b"battleship_range_proof");
-        proof
-            .verify_single(
-                &bp_gens,
-                &pc_gens,
-                &mut verifier_transcript,
-                &commitment,
-                bits,
-            )
-            .map_err(|_| "Range proof failed".to_string())?;
-
-        Ok(CoordinateProof { commitment, proof })
+        let bp_gens = BulletproofGens::new(bits, parties);
+        let mut transcript = Transcript::new(b"battleship_fleet_range_proof");
+        let (proof, _) = RangeProof::prove_multiple(&bp_gens, &pc_gens, &mut transcript, &values, &blindings, bits)
+            .map_err(|e| format!("Failed to generate fleet range proof (bits={}, values={}): {:?}", bits, parties, e))?;
+
+        self.fleet_range_proof = Some(proof);
+        Ok(())
     }
 
     // Creates cryptographic commitment for ship position and stores it with range proofs
-    fn place_ship(&mut self, x: u8, y: u8) -> Result<(), String> {
-        let x_proof = self.prove_coordinate_range(x)?;
-        let y_proof = self.prove_coordinate_range(y)?;
+    // the range proofs cover the origin, the rest of the segment is range checked inside the SNARKs
+    fn place_ship(&mut self, x: u8, y: u8, length: u8, orientation: Orientation) -> Result<(), String> {
+        match self.next_ship_length() {
+            None => return Err("The whole fleet is already placed".to_string()),
+            Some(expected) if expected != length => {
+                return Err(format!("Next ship must have length {}, got {}", expected, length));
+            }
+            Some(_) => {}
+        }
+
+        // the far end is checked before the cells are listed, they are u8 and would overflow past the edge
+        let ship = Ship { x, y, orientation, length };
+        let (end_x, end_y) = match orientation {
+            Orientation::Horizontal => (x as usize + length as usize - 1, y as usize),
+            Orientation::Vertical => (x as usize, y as usize + length as usize - 1),
+        };
+        if length == 0 || !self.grid_size.contains(end_x, end_y) {
+            return Err(format!("Ship of length {} at ({}, {}) runs off the {} board", length, x, y, self.grid_size));
+        }
+        let cells = ship.cells();
 
-        let commitment = Self::commit_position(x, y);
-        self.ship_commitments.push(commitment);        
-        self.ship_positions.push((x, y));
-        self.grid[y as usize][x as usize] = 1;
+        // two ships on the same cell would silently shrink the fleet
+        if let Some(&(cx, cy)) = cells.iter().find(|&&(cx, cy)| self.grid[cy as usize][cx as usize] != 0) {
+            return Err(format!("There is already a ship at ({}, {})", cx, cy));
+        }
+
+        if self.adjacency == AdjacencyRule::Forbidden {
+            let touching = self.ships.iter().flat_map(|other| other.cells()).find(|&(ox, oy)| {
+                cells.iter().any(|&(cx, cy)| cx.abs_diff(ox) <= 1 && cy.abs_diff(oy) <= 1)
+            });
+            if let Some((ox, oy)) = touching {
+                return Err(format!("Ships may not touch; there is a ship at ({}, {})", ox, oy));
+            }
+        }
 
-        self.ship_range_proofs.push(ShipPlacementProof { x: x_proof, y: y_proof });
+        // only a placement that passed every check gets committed, the bridge proofs are the slow part
+        let (x_commitment, x_blinding, x_pallas_blinding) = self.commit_coordinate(x, self.grid_size.width)?;
+        let (y_commitment, y_blinding, y_pallas_blinding) = self.commit_coordinate(y, self.grid_size.height)?;
 
-        println!("Ship placement proof valid");
+        let commitment = Self::commit_ship(&ship);
+        self.ship_commitments.push(commitment);
+        self.ships.push(ship);
+        for (cx, cy) in cells {
+            self.grid[cy as usize][cx as usize] = 1;
+        }
+
+        self.ship_range_proofs.push(ShipPlacementProof { x: x_commitment, y: y_commitment });
+        self.range_blindings.push((x_blinding, y_blinding));
+        self.bridge_blindings.push((x_pallas_blinding, y_pallas_blinding));
+
+        // the last ship completes the fleet, range prove all of it in one go
+        if self.next_ship_length().is_none() {
+            self.prove_fleet_ranges()?;
+        }
 
         Ok(())
     }
 
-    // check all stored ship placement proofs using current grid-size bits
-    fn verify_published_ship_proofs(&self) -> bool {
-        let pc_gens = PedersenGens::default();
-        let bits = self.bits_for_grid_size();
-        let bp_gens = BulletproofGens::new(bits, 1);
-        for sp in &self.ship_range_proofs {
-            // x
-            let mut vt_x = Transcript::new(b"battleship_range_proof");
-            if sp.x
-                .proof
-                .verify_single(&bp_gens, &pc_gens, &mut vt_x, &sp.x.commitment, bits)
-                .is_err()
-            {
-                return false;
-            }
-            // y
-            let mut vt_y = Transcript::new(b"battleship_range_proof");
-            if sp.y
-                .proof
-                .verify_single(&bp_gens, &pc_gens, &mut vt_y, &sp.y.commitment, bits)
-                .is_err()
-            {
-                return false;
-            }
+    // private ship witnesses for every ship, in commitment order
+    fn ship_witnesses(&self) -> Vec<ShipWitness> {
+        self.ship_commitments
+            .iter()
+            .zip(self.ships.iter())
+            .map(|(ship_commitment, ship)| ShipWitness::known(ship, ship_commitment.salt))
+            .collect()
+    }
+
+    // proves the committed fleet is on the board without overlapping (or touching, if the rule says so)
+    fn prove_fleet_setup(&self, params: &Params<EqAffine>, pk: &ProvingKey<EqAffine>) -> Vec<u8> {
+        let circuit = FleetSetupCircuit {
+            ships: self.ship_witnesses(),
+            fleet: self.fleet.clone(),
+            grid_size: self.grid_size,
+            adjacency: self.adjacency,
+        };
+        let public_inputs = setup_public_inputs(&self.commitment_values());
+        generate_proof(params, pk, circuit, &public_inputs)
+    }
+
+    // board mode setup proof: the published root covers as many occupied cells as the fleet has ship cells
+    fn prove_board_setup(&self, params: &Params<EqAffine>, pk: &ProvingKey<EqAffine>) -> Vec<u8> {
+        let board = self.board_commitment.as_ref().expect("board is committed before its setup is proven");
+        let circuit = BoardSetupCircuit {
+            occupied: board.occupied.iter().map(|&occupied| Value::known(Fp::from(occupied as u64))).collect(),
+            salts: board.salts.iter().map(|&salt| Value::known(Fp::from(salt))).collect(),
+            grid_size: self.grid_size,
+            ship_cells: fleet_cells(&self.fleet),
+        };
+        generate_proof(params, pk, circuit, &board_setup_public_inputs(board.root))
+    }
+
+    // proves for every ship that the bridged Pallas commitments of its range proved x and y
+    // open to the same x and y as its Poseidon commitment
+    fn prove_ship_links(&self, params: &Params<EqAffine>, pk: &ProvingKey<EqAffine>) -> Vec<Vec<u8>> {
+        self.ship_witnesses()
+            .into_iter()
+            .zip(self.bridge_blindings.iter())
+            .zip(self.ship_range_proofs.iter().zip(self.ship_commitments.iter()))
+            .map(|((ship, &(x_blinding, y_blinding)), (placement, commitment))| {
+                let circuit = ShipLinkCircuit {
+                    ship,
+                    blindings: [Value::known(x_blinding), Value::known(y_blinding)],
+                    value_bits: self.bits_for_grid_size(),
+                };
+                let public_inputs = link_public_inputs(commitment.commitment, &placement.x.bridge, &placement.y.bridge);
+                generate_proof(params, pk, circuit, &public_inputs)
+            })
+            .collect()
+    }
+
+    // commits to the whole grid as a Merkle root, call once every ship is placed
+    fn commit_board(&mut self) {
+        self.board_commitment = Some(BoardCommitment::new(self.grid_size, &self.grid));
+    }
+
+    // Poseidon commitments of the ships without their salts
+    fn commitment_values(&self) -> Vec<Fp> {
+        self.ship_commitments.iter().map(|c| c.commitment).collect()
+    }
+
+    // everything the opponent gets to see of this board, call once setup is done.
+    // in board mode only the root goes out, nothing checks per ship commitments or range proofs there
+    fn publish(&self) -> PublishedBoard {
+        match &self.board_commitment {
+            None => PublishedBoard {
+                grid_size: self.grid_size,
+                fleet: self.fleet.clone(),
+                adjacency: self.adjacency,
+                ship_commitments: self.commitment_values(),
+                ship_range_proofs: self.ship_range_proofs.clone(),
+                fleet_range_proof: self.fleet_range_proof.clone(),
+                board_root: None,
+            },
+            Some(board) => PublishedBoard {
+                grid_size: self.grid_size,
+                fleet: self.fleet.clone(),
+                adjacency: self.adjacency,
+                ship_commitments: Vec::new(),
+                ship_range_proofs: Vec::new(),
+                fleet_range_proof: None,
+                board_root: Some(board.root),
+            },
         }
-        true
     }
 
-    // verifies the attack by checking if the coordinates are in range
-    // and if any ship commitment matches this position
-    fn verify_attack_range(&mut self, x: u8, y: u8) -> Result<bool, String> {
-        self.prove_coordinate_range(x)?;
-        self.prove_coordinate_range(y)?;
+    // answers a shot from the private board and proves the answer against whatever was published
+    // at setup (the per ship commitments, or the board root in board mode)
+    fn prove_shot(
+        &self,
+        params: &Params<EqAffine>,
+        pk: &ProvingKey<EqAffine>,
+        attack_x: u8,
+        attack_y: u8,
+    ) -> Result<ShotProof, String> {
+        if !self.grid_size.contains(attack_x as usize, attack_y as usize) {
+            return Err(format!("Coordinate ({}, {}) is off the {} board", attack_x, attack_y, self.grid_size));
+        }
+        let hit = self.grid[attack_y as usize][attack_x as usize] != 0;
+
+        let (proof, public_inputs) = match &self.board_commitment {
+            None => {
+                let circuit = BattleshipCircuit {
+                    ships: self.ship_witnesses(),
+                    fleet: self.fleet.clone(),
+                    attack_x: Value::known(Fp::from(attack_x as u64)),
+                    attack_y: Value::known(Fp::from(attack_y as u64)),
+                    hit: Value::known(Fp::from(if hit { 1u64 } else { 0u64 })),
+                    grid_size: self.grid_size,
+                };
 
-        // Check if any stored commitment matches this position
-        let hit = self.ship_commitments.iter().any(|commitment| {
-            Self::verify_commitment(x, y, commitment.salt, commitment.commitment)
-        });
+                // Public inputs including hit/miss result, the shot and the published commitments
+                let public_inputs = shot_public_inputs(hit, attack_x, attack_y, &self.commitment_values());
+                (generate_proof(params, pk, circuit, &public_inputs), public_inputs)
+            }
+            Some(board) => {
+                let index = self.grid_size.index(attack_x as usize, attack_y as usize);
+                let depth = board_depth(self.grid_size);
+                let circuit = MerkleShotCircuit {
+                    occupied: Value::known(Fp::from(board.occupied[index] as u64)),
+                    salt: Value::known(Fp::from(board.salts[index])),
+                    siblings: board.path(index).into_iter().map(Value::known).collect(),
+                    index_bits: (0..depth)
+                        .map(|level| Value::known(Fp::from(((index >> level) & 1) as u64)))
+                        .collect(),
+                    attack_x: Value::known(Fp::from(attack_x as u64)),
+                    attack_y: Value::known(Fp::from(attack_y as u64)),
+                    grid_size: self.grid_size,
+                };
+
+                // Public inputs including hit/miss result, the shot and the board root
+                let public_inputs = board_shot_public_inputs(hit, attack_x, attack_y, board.root);
+                (generate_proof(params, pk, circuit, &public_inputs), public_inputs)
+            }
+        };
+
+        Ok(ShotProof { attack_x, attack_y, hit, proof, public_inputs })
+    }
 
-        Ok(hit)
+    // opens every commitment for the post game audit
+    fn reveal(&self) -> BoardReveal {
+        BoardReveal {
+            ships: self
+                .ships
+                .iter()
+                .zip(self.ship_commitments.iter())
+                .map(|(ship, commitment)| (*ship, commitment.salt))
+                .collect(),
+            cells: self
+                .board_commitment
+                .as_ref()
+                .map(|board| (board.occupied.clone(), board.salts.clone())),
+        }
     }
 
     // Update grid after SNARK verification
     fn record_hit(&mut self, x: u8, y: u8) {
+        // hitting the same cell twice doesn't add to the public hit history
+        if self.grid[y as usize][x as usize] != 2 {
+            self.hits_taken.push((x, y));
+        }
         self.grid[y as usize][x as usize] = 2;
     }
 
+    // ships that have every cell hit but haven't been announced yet
+    fn newly_sunk_ships(&self) -> Vec<usize> {
+        self.ships
+            .iter()
+            .enumerate()
+            .filter(|&(i, ship)| {
+                !self.sunk_announced[i]
+                    && ship.cells().iter().all(|cell| self.hits_taken.contains(cell))
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    // proves ship `index` is sunk against its published commitment and the public hit history
+    fn prove_sunk(&self, params: &Params<EqAffine>, pk: &ProvingKey<EqAffine>, index: usize) -> Vec<u8> {
+        let ship = &self.ships[index];
+        let max_hits = fleet_cells(&self.fleet);
+        let circuit = SunkShipCircuit {
+            ship: ShipWitness::known(ship, self.ship_commitments[index].salt),
+            length: ship.length,
+            max_hits,
+        };
+        let public_inputs = sunk_public_inputs(
+            self.ship_commitments[index].commitment,
+            &self.hits_taken,
+            max_hits,
+            self.grid_size,
+        );
+        generate_proof(params, pk, circuit, &public_inputs)
+    }
+
     // checks if all ships are sunk
     fn all_ships_sunk(&self) -> bool {
         for row in &self.grid {
@@ -263,6 +891,26 @@ impl BattleshipGame {
         true
     }
 
+    // proves the fleet status (all sunk or not) against the published commitments and the public hit history
+    fn prove_fleet_status(&self, params: &Params<EqAffine>, pk: &ProvingKey<EqAffine>) -> (Vec<u8>, bool) {
+        let all_sunk = self.all_ships_sunk();
+        let max_hits = fleet_cells(&self.fleet);
+        let circuit = FleetStatusCircuit {
+            ships: self.ship_witnesses(),
+            fleet: self.fleet.clone(),
+            max_hits,
+            all_sunk: Value::known(Fp::from(if all_sunk { 1u64 } else { 0u64 })),
+        };
+        let public_inputs = status_public_inputs(
+            all_sunk,
+            &self.commitment_values(),
+            &self.hits_taken,
+            max_hits,
+            self.grid_size,
+        );
+        (generate_proof(params, pk, circuit, &public_inputs), all_sunk)
+    }
+
     // prints the grid to the console at the end of the game
     fn print_grid(&self) {
         for row in &self.grid {
@@ -279,37 +927,30 @@ impl BattleshipGame {
         }
     }
     
-    // Creates a cryptographically secure commitment to a position using Poseidon hash with random salt
-    fn commit_position(x: u8, y: u8) -> ShipCommitment {
+    // Creates a cryptographically secure commitment to a ship using Poseidon hash with random salt
+    fn commit_ship(ship: &Ship) -> ShipCommitment {
         let salt = OsRng.next_u64();
-    
-        let message = [
-            Fp::from(x as u64),
-            Fp::from(y as u64),
-            Fp::from(salt),
-        ];
-        
-        let commitment = PoseidonPrimitiveHash::<Fp, P128Pow5T3, ConstantLength<3>, 3, 2>::init()
-            .hash(message);
-        
+        let commitment = Self::compute_commitment(ship, salt);
         ShipCommitment {commitment, salt}
     }
 
-    // Computes Poseidon hash of position with salt
-    fn compute_commitment(x: u8, y: u8, salt: u64) -> Fp {
+    // Computes Poseidon hash of (origin x, origin y, orientation, length, salt)
+    fn compute_commitment(ship: &Ship, salt: u64) -> Fp {
         let message = [
-            Fp::from(x as u64),
-            Fp::from(y as u64),
+            Fp::from(ship.x as u64),
+            Fp::from(ship.y as u64),
+            Fp::from(ship.orientation.as_u64()),
+            Fp::from(ship.length as u64),
             Fp::from(salt),
         ];
     
-        PoseidonPrimitiveHash::<Fp, P128Pow5T3, ConstantLength<3>, 3, 2>::init()
+        PoseidonPrimitiveHash::<Fp, P128Pow5T3, ConstantLength<5>, 3, 2>::init()
             .hash(message)
     }
 
-    // Verifies that a position matches a commitment
-    fn verify_commitment(x: u8, y: u8, salt: u64, commitment: Fp) -> bool {
-        let computed = Self::compute_commitment(x, y, salt);
+    // Verifies that a ship matches a commitment
+    fn verify_commitment(ship: &Ship, salt: u64, commitment: Fp) -> bool {
+        let computed = Self::compute_commitment(ship, salt);
         computed == commitment
     }
 
@@ -384,80 +1025,779 @@ impl<F: Field> IsZeroChip<F> {
     }
 }
 
-// SNARK circuit verifies that attack coordinates match ship coordinates
-#[derive(Clone)]
-struct BattleshipCircuit {
-    ships: Vec<(Value<Fp>, Value<Fp>, Value<Fp>)>, // (x, y, salt) for each ship           
-    published_commitments: Vec<Value<Fp>>, // commitments for each ship
-    attack_x: Value<Fp>, // attack x-coordinate
-    attack_y: Value<Fp>, // attack y-coordinate
-    hit: Value<Fp>,      // 1 if hit, 0 if miss
+// boolean AND/OR chip used to fold the per-coordinate IsZero outputs into a single hit bit
+// every output is copy constrained to its inputs so a prover can't just write any value in there
+//
+//  op  | lhs | rhs | out
+// -----+-----+-----+----------------------
+//  and |  a  |  b  | a * b
+//  or  |  a  |  b  | a + b - a * b
+
+#[derive(Clone, Debug)]
+pub struct BoolOpsConfig {
+    pub lhs: Column<Advice>,
+    pub rhs: Column<Advice>,
+    pub out: Column<Advice>,
+    pub q_and: Selector,
+    pub q_or: Selector,
 }
 
-impl Circuit<Fp> for BattleshipCircuit {
+pub struct BoolOpsChip<F: Field> {
+    config: BoolOpsConfig,
+    _marker: std::marker::PhantomData<F>,
+}
 
-    type Config = (
-        Pow5Config<Fp, 3, 2>,
-        Column<Advice>,        // advice_col
-        Column<Advice>,        // result_col
-        Column<Advice>,        // diff_col
-        Column<Advice>,        // value_inv_col
-        Column<Advice>,        // is_zero_result_col
-        Column<Instance>,
-        IsZeroConfig<Fp>,      // IsZero config
-        Selector,              // q_is_zero selector
-    );
+impl<F: Field> BoolOpsChip<F> {
+    pub fn construct(config: BoolOpsConfig) -> Self {
+        BoolOpsChip { config, _marker: std::marker::PhantomData }
+    }
 
-    type FloorPlanner = SimpleFloorPlanner;
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        lhs: Column<Advice>,
+        rhs: Column<Advice>,
+        out: Column<Advice>,
+    ) -> BoolOpsConfig {
+        let q_and = meta.selector();
+        let q_or = meta.selector();
+
+        meta.create_gate("bool_and", |meta| {
+            let q = meta.query_selector(q_and);
+            let a = meta.query_advice(lhs, Rotation::cur());
+            let b = meta.query_advice(rhs, Rotation::cur());
+            let c = meta.query_advice(out, Rotation::cur());
+            vec![q * (a * b - c)]
+        });
 
-    // no witnesses are needed for the circuit
-    fn without_witnesses(&self) -> Self {
-        Self {
-            ships: Vec::new(),
-            published_commitments: Vec::new(),
-            attack_x: Value::unknown(),
-            attack_y: Value::unknown(),
-            hit: Value::unknown(),
-        }
+        meta.create_gate("bool_or", |meta| {
+            let q = meta.query_selector(q_or);
+            let a = meta.query_advice(lhs, Rotation::cur());
+            let b = meta.query_advice(rhs, Rotation::cur());
+            let c = meta.query_advice(out, Rotation::cur());
+            vec![q * (a.clone() + b.clone() - a * b - c)]
+        });
+
+        BoolOpsConfig { lhs, rhs, out, q_and, q_or }
     }
 
-    // configures the circuit with proper constraint gates for hit verification
-    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+    pub fn and(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let out = a.value().zip(b.value()).map(|(a, b)| *a * *b);
+        self.assign_op(&mut layouter, "and", self.config.q_and, a, b, out)
+    }
 
-        let state = [
-            meta.advice_column(),
-            meta.advice_column(),
-            meta.advice_column(),
-        ];
+    pub fn or(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let out = a.value().zip(b.value()).map(|(a, b)| *a + *b - *a * *b);
+        self.assign_op(&mut layouter, "or", self.config.q_or, a, b, out)
+    }
 
-        let partial_sbox = meta.advice_column();
-        
-        let rc_a = [
-            meta.fixed_column(),
-            meta.fixed_column(),
-            meta.fixed_column(),
-        ];
+    fn assign_op(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        name: &str,
+        selector: Selector,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        out: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || name,
+            |mut region| {
+                selector.enable(&mut region, 0)?;
+                a.copy_advice(|| "lhs", &mut region, self.config.lhs, 0)?;
+                b.copy_advice(|| "rhs", &mut region, self.config.rhs, 0)?;
+                region.assign_advice(|| "out", self.config.out, 0, || out)
+            },
+        )
+    }
+}
 
-        let rc_b = [
-            meta.fixed_column(),
-            meta.fixed_column(),
-            meta.fixed_column(),
-        ];
-        
-        meta.enable_constant(rc_b[0]);
-        
-        let poseidon_config = Pow5Chip::configure::<P128Pow5T3>(
-            meta,
-            state,
-            partial_sbox,
-            rc_a,
-            rc_b,
-        );
+// range check chip: proves a cell holds an x in 0..width or a y in 0..height with a lookup into a fixed
+// (axis, value) table loaded with (0, 0..width) and (1, 0..height), so the board size ends up baked into the keys
+// (complex selectors are needed here since halo2 doesn't allow simple selectors in lookups)
 
-        let advice = meta.advice_column();
-        let result = meta.advice_column();
-        let diff_col = meta.advice_column();
-        let value_inv_col = meta.advice_column();
+#[derive(Clone, Debug)]
+pub struct RangeCheckConfig {
+    pub value: Column<Advice>,
+    pub axis_table: TableColumn,
+    pub value_table: TableColumn,
+    pub q_x: Selector,
+    pub q_y: Selector,
+}
+
+pub struct RangeCheckChip<F: PrimeField> {
+    config: RangeCheckConfig,
+    grid_size: GridSize,
+    _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: PrimeField> RangeCheckChip<F> {
+    pub fn construct(config: RangeCheckConfig, grid_size: GridSize) -> Self {
+        RangeCheckChip { config, grid_size, _marker: std::marker::PhantomData }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, value: Column<Advice>) -> RangeCheckConfig {
+        let q_x = meta.complex_selector();
+        let q_y = meta.complex_selector();
+        let axis_table = meta.lookup_table_column();
+        let value_table = meta.lookup_table_column();
+
+        // the axis is 1 only when checking a y, when neither selector is on the input is (0, 0)
+        // which is always in the table
+        meta.lookup(|meta| {
+            let q_x = meta.query_selector(q_x);
+            let q_y = meta.query_selector(q_y);
+            let value = meta.query_advice(value, Rotation::cur());
+            vec![(q_y.clone(), axis_table), ((q_x + q_y) * value, value_table)]
+        });
+
+        RangeCheckConfig { value, axis_table, value_table, q_x, q_y }
+    }
+
+    // fills the lookup table with every valid x and then every valid y
+    pub fn load_table(&self, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "coordinate_table",
+            |mut table| {
+                let rows = (0..self.grid_size.width)
+                    .map(|x| (0u64, x))
+                    .chain((0..self.grid_size.height).map(|y| (1u64, y)));
+                for (row, (axis, i)) in rows.enumerate() {
+                    table.assign_cell(|| "axis", self.config.axis_table, row, || Value::known(F::from(axis)))?;
+                    table.assign_cell(
+                        || "coordinate",
+                        self.config.value_table,
+                        row,
+                        || Value::known(F::from(i as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    // the cell is an x coordinate, so it has to be < width
+    pub fn check_x(&self, layouter: impl Layouter<F>, cell: &AssignedCell<F, F>) -> Result<(), Error> {
+        self.check(layouter, cell, self.config.q_x)
+    }
+
+    // the cell is a y coordinate, so it has to be < height
+    pub fn check_y(&self, layouter: impl Layouter<F>, cell: &AssignedCell<F, F>) -> Result<(), Error> {
+        self.check(layouter, cell, self.config.q_y)
+    }
+
+    // copies the cell into the range checked column and enables the lookup for its axis on it
+    fn check(&self, mut layouter: impl Layouter<F>, cell: &AssignedCell<F, F>, selector: Selector) -> Result<(), Error> {
+        layouter.assign_region(
+            || "range_check",
+            |mut region| {
+                selector.enable(&mut region, 0)?;
+                cell.copy_advice(|| "coordinate", &mut region, self.config.value, 0)?;
+                Ok(())
+            },
+        )
+    }
+}
+
+// sets up the Poseidon chip, shared by every circuit in the game
+fn configure_poseidon(meta: &mut ConstraintSystem<Fp>) -> Pow5Config<Fp, 3, 2> {
+    let state = [
+        meta.advice_column(),
+        meta.advice_column(),
+        meta.advice_column(),
+    ];
+
+    let partial_sbox = meta.advice_column();
+    
+    let rc_a = [
+        meta.fixed_column(),
+        meta.fixed_column(),
+        meta.fixed_column(),
+    ];
+
+    let rc_b = [
+        meta.fixed_column(),
+        meta.fixed_column(),
+        meta.fixed_column(),
+    ];
+    
+    meta.enable_constant(rc_b[0]);
+    
+    Pow5Chip::configure::<P128Pow5T3>(
+        meta,
+        state,
+        partial_sbox,
+        rc_a,
+        rc_b,
+    )
+}
+
+// private witness for one ship: what goes into its commitment
+#[derive(Clone, Copy, Debug)]
+struct ShipWitness {
+    x: Value<Fp>,           // origin x-coordinate
+    y: Value<Fp>,           // origin y-coordinate
+    orientation: Value<Fp>, // 0 horizontal, 1 vertical
+    length: Value<Fp>,      // number of cells
+    salt: Value<Fp>,
+}
+
+impl ShipWitness {
+    fn known(ship: &Ship, salt: u64) -> Self {
+        ShipWitness {
+            x: Value::known(Fp::from(ship.x as u64)),
+            y: Value::known(Fp::from(ship.y as u64)),
+            orientation: Value::known(Fp::from(ship.orientation.as_u64())),
+            length: Value::known(Fp::from(ship.length as u64)),
+            salt: Value::known(Fp::from(salt)),
+        }
+    }
+
+    fn unknown() -> Self {
+        ShipWitness {
+            x: Value::unknown(),
+            y: Value::unknown(),
+            orientation: Value::unknown(),
+            length: Value::unknown(),
+            salt: Value::unknown(),
+        }
+    }
+}
+
+// cells left behind by hashing a ship in-circuit
+// kept so later checks can copy constrain against the committed values
+struct HashedShip {
+    x: AssignedCell<Fp, Fp>,
+    y: AssignedCell<Fp, Fp>,
+    orientation: AssignedCell<Fp, Fp>,
+    length: AssignedCell<Fp, Fp>,
+    commitment: AssignedCell<Fp, Fp>,
+}
+
+// assigns a ship's (x, y, orientation, length, salt) and hashes it in-circuit
+fn hash_ship(
+    layouter: &mut impl Layouter<Fp>,
+    poseidon_config: &Pow5Config<Fp, 3, 2>,
+    advice_col: Column<Advice>,
+    ship: &ShipWitness,
+    i: usize,
+) -> Result<HashedShip, Error> {
+    // assign ship values to cells first or else poseidon hashing won't work (i think its because of how halo2 works with layouters and regions)
+    let message: [halo2_proofs::circuit::AssignedCell<Fp, Fp>; 5] = layouter.assign_region(
+        || format!("assign_ship_values_{}", i),
+        |mut region| {
+            let x_cell = region.assign_advice(|| "ship_x", advice_col, 0, || ship.x)?;
+            let y_cell = region.assign_advice(|| "ship_y", advice_col, 1, || ship.y)?;
+            let orientation_cell = region.assign_advice(
+                || "orientation",
+                advice_col,
+                2,
+                || ship.orientation,
+            )?;
+            let length_cell = region.assign_advice(|| "length", advice_col, 3, || ship.length)?;
+            let salt_cell = region.assign_advice(|| "salt", advice_col, 4, || ship.salt)?;
+            Ok([x_cell, y_cell, orientation_cell, length_cell, salt_cell])
+        },
+    )?;
+
+    let [x, y, orientation, length, _] = message.clone();
+
+    // Hash ship data hash(x, y, orientation, length, salt)
+    let commitment = poseidon_hash_cells(layouter, poseidon_config, message, &format!("ship_{}", i))?;
+
+    Ok(HashedShip { x, y, orientation, length, commitment })
+}
+
+// in-circuit Poseidon hash of already assigned cells (the gadget copy constrains them into its state)
+fn poseidon_hash_cells<const L: usize>(
+    layouter: &mut impl Layouter<Fp>,
+    poseidon_config: &Pow5Config<Fp, 3, 2>,
+    message: [AssignedCell<Fp, Fp>; L],
+    name: &str,
+) -> Result<AssignedCell<Fp, Fp>, Error> {
+    let poseidon_chip = Pow5Chip::construct(poseidon_config.clone());
+    let hasher = halo2_gadgets::poseidon::Hash::<Fp, Pow5Chip<Fp, 3, 2>, P128Pow5T3, ConstantLength<L>, 3, 2>::init(
+        poseidon_chip,
+        layouter.namespace(|| format!("poseidon_init_{}", name)),
+    )?;
+
+    hasher.hash(layouter.namespace(|| format!("hash_{}", name)), message)
+}
+
+// ship cells chip: expands a committed ship into the cells it covers
+// the fleet spec (ship lengths) is public so each ship gets a fixed number of rows,
+// one per cell, with the offset along the ship in a fixed column
+//
+//  x | y | orientation | offset (fixed) | cell_x                            | cell_y
+// ---+---+-------------+----------------+-----------------------------------+-------------------------
+//  x | y |      o      |       k        | x + k * (1 - o)                   | y + k * o
+
+// assigned (x, y) of one board cell
+pub type CellCoords<F> = (AssignedCell<F, F>, AssignedCell<F, F>);
+
+#[derive(Clone, Debug)]
+pub struct ShipCellsConfig {
+    pub x: Column<Advice>,
+    pub y: Column<Advice>,
+    pub orientation: Column<Advice>,
+    pub offset: Column<Fixed>,
+    pub cell_x: Column<Advice>,
+    pub cell_y: Column<Advice>,
+    pub q_cell: Selector,
+}
+
+pub struct ShipCellsChip<F: PrimeField> {
+    config: ShipCellsConfig,
+    _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: PrimeField> ShipCellsChip<F> {
+    pub fn construct(config: ShipCellsConfig) -> Self {
+        ShipCellsChip { config, _marker: std::marker::PhantomData }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        x: Column<Advice>,
+        y: Column<Advice>,
+        orientation: Column<Advice>,
+        cell_x: Column<Advice>,
+        cell_y: Column<Advice>,
+    ) -> ShipCellsConfig {
+        let offset = meta.fixed_column();
+        let q_cell = meta.selector();
+
+        meta.create_gate("ship_cell", |meta| {
+            let q = meta.query_selector(q_cell);
+            let x = meta.query_advice(x, Rotation::cur());
+            let y = meta.query_advice(y, Rotation::cur());
+            let o = meta.query_advice(orientation, Rotation::cur());
+            let k = meta.query_fixed(offset);
+            let cell_x = meta.query_advice(cell_x, Rotation::cur());
+            let cell_y = meta.query_advice(cell_y, Rotation::cur());
+            let one = Expression::Constant(F::ONE);
+
+            vec![
+                // orientation has to be 0 or 1
+                q.clone() * o.clone() * (one.clone() - o.clone()),
+                q.clone() * (cell_x - (x + k.clone() * (one - o.clone()))),
+                q * (cell_y - (y + k * o)),
+            ]
+        });
+
+        ShipCellsConfig { x, y, orientation, offset, cell_x, cell_y, q_cell }
+    }
+
+    // returns the (x, y) cells of a ship of the given length
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: &AssignedCell<F, F>,
+        y: &AssignedCell<F, F>,
+        orientation: &AssignedCell<F, F>,
+        length: u8,
+    ) -> Result<Vec<CellCoords<F>>, Error> {
+        layouter.assign_region(
+            || "ship_cells",
+            |mut region| {
+                let mut cells = Vec::new();
+                for k in 0..length as usize {
+                    self.config.q_cell.enable(&mut region, k)?;
+                    x.copy_advice(|| "x", &mut region, self.config.x, k)?;
+                    y.copy_advice(|| "y", &mut region, self.config.y, k)?;
+                    orientation.copy_advice(|| "orientation", &mut region, self.config.orientation, k)?;
+                    let offset = F::from(k as u64);
+                    region.assign_fixed(|| "offset", self.config.offset, k, || Value::known(offset))?;
+
+                    let o = orientation.value().copied();
+                    let cell_x = x.value().zip(o).map(|(x, o)| *x + offset * (F::ONE - o));
+                    let cell_y = y.value().zip(o).map(|(y, o)| *y + offset * o);
+                    let cell_x = region.assign_advice(|| "cell_x", self.config.cell_x, k, || cell_x)?;
+                    let cell_y = region.assign_advice(|| "cell_y", self.config.cell_y, k, || cell_y)?;
+                    cells.push((cell_x, cell_y));
+                }
+                Ok(cells)
+            },
+        )
+    }
+}
+
+// pins a committed ship length to the public fleet spec
+fn constrain_ship_length(
+    layouter: &mut impl Layouter<Fp>,
+    length_cell: &AssignedCell<Fp, Fp>,
+    length: u8,
+) -> Result<(), Error> {
+    layouter.assign_region(
+        || "fleet_length",
+        |mut region| region.constrain_constant(length_cell.cell(), Fp::from(length as u64)),
+    )
+}
+
+// hit list chip: shows a board cell is in a list of hit cells with a running product
+// prod_j ((cell_x + 2^16 * cell_y) - (hit_x_j + 2^16 * hit_y_j)) is zero iff one of the factors is,
+// and packing is one to one as long as the coordinates are < 2^16 (the cells are range checked
+// by the setup proof for the same commitment, the hits are public u8s)
+//
+//  cell_x | cell_y | hit_x | hit_y | acc
+// --------+--------+-------+-------+---------------------------------------------
+//    cx   |   cy   |  hx_0 |  hy_0 | 1
+//    cx   |   cy   |  hx_1 |  hy_1 | acc_0 * ((cx + 2^16 cy) - (hx_0 + 2^16 hy_0))
+//   ...   |  ...   |  ...  |  ...  | ...
+
+#[derive(Clone, Debug)]
+pub struct HitListConfig {
+    pub cell_x: Column<Advice>,
+    pub cell_y: Column<Advice>,
+    pub hit_x: Column<Advice>,
+    pub hit_y: Column<Advice>,
+    pub acc: Column<Advice>,
+    pub q_step: Selector,
+}
+
+pub struct HitListChip<F: PrimeField> {
+    config: HitListConfig,
+    _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: PrimeField> HitListChip<F> {
+    pub fn construct(config: HitListConfig) -> Self {
+        HitListChip { config, _marker: std::marker::PhantomData }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        cell_x: Column<Advice>,
+        cell_y: Column<Advice>,
+        hit_x: Column<Advice>,
+        hit_y: Column<Advice>,
+        acc: Column<Advice>,
+    ) -> HitListConfig {
+        let q_step = meta.selector();
+
+        meta.create_gate("hit_product", |meta| {
+            let q = meta.query_selector(q_step);
+            let cell_x = meta.query_advice(cell_x, Rotation::cur());
+            let cell_y = meta.query_advice(cell_y, Rotation::cur());
+            let hit_x = meta.query_advice(hit_x, Rotation::cur());
+            let hit_y = meta.query_advice(hit_y, Rotation::cur());
+            let acc_cur = meta.query_advice(acc, Rotation::cur());
+            let acc_next = meta.query_advice(acc, Rotation::next());
+            let shift = Expression::Constant(F::from(1 << 16));
+
+            let packed_cell = cell_x + shift.clone() * cell_y;
+            let packed_hit = hit_x + shift * hit_y;
+            vec![q * (acc_next - acc_cur * (packed_cell - packed_hit))]
+        });
+
+        HitListConfig { cell_x, cell_y, hit_x, hit_y, acc, q_step }
+    }
+
+    // copies the public hit list (x_0, y_0, x_1, y_1, ...) out of the instance column
+    pub fn load_hits(
+        &self,
+        mut layouter: impl Layouter<F>,
+        instance: Column<Instance>,
+        offset: usize,
+        count: usize,
+    ) -> Result<Vec<CellCoords<F>>, Error> {
+        layouter.assign_region(
+            || "load_hits",
+            |mut region| {
+                (0..count)
+                    .map(|j| {
+                        let hit_x = region.assign_advice_from_instance(
+                            || "hit_x",
+                            instance,
+                            offset + 2 * j,
+                            self.config.hit_x,
+                            j,
+                        )?;
+                        let hit_y = region.assign_advice_from_instance(
+                            || "hit_y",
+                            instance,
+                            offset + 2 * j + 1,
+                            self.config.hit_y,
+                            j,
+                        )?;
+                        Ok((hit_x, hit_y))
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    // returns the running product for one cell against every hit, it is zero iff the cell was hit
+    pub fn product(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &CellCoords<F>,
+        hits: &[CellCoords<F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || "hit_product",
+            |mut region| {
+                let (cell_x, cell_y) = cell;
+                let mut acc = region.assign_advice_from_constant(|| "acc_start", self.config.acc, 0, F::ONE)?;
+
+                for (j, (hit_x, hit_y)) in hits.iter().enumerate() {
+                    self.config.q_step.enable(&mut region, j)?;
+                    cell_x.copy_advice(|| "cell_x", &mut region, self.config.cell_x, j)?;
+                    cell_y.copy_advice(|| "cell_y", &mut region, self.config.cell_y, j)?;
+                    hit_x.copy_advice(|| "hit_x", &mut region, self.config.hit_x, j)?;
+                    hit_y.copy_advice(|| "hit_y", &mut region, self.config.hit_y, j)?;
+
+                    let shift = F::from(1 << 16);
+                    let packed_cell = cell_x.value().zip(cell_y.value()).map(|(x, y)| *x + shift * *y);
+                    let packed_hit = hit_x.value().zip(hit_y.value()).map(|(x, y)| *x + shift * *y);
+                    let next = acc
+                        .value()
+                        .zip(packed_cell.zip(packed_hit))
+                        .map(|(acc, (cell, hit))| *acc * (cell - hit));
+                    acc = region.assign_advice(|| "acc", self.config.acc, j + 1, || next)?;
+                }
+
+                Ok(acc)
+            },
+        )
+    }
+}
+
+// pallas mul chip: recomputes a Pallas Pedersen commitment value * G + blinding * H in-circuit.
+// the circuit field is the Pallas base field so point coordinates are native, and both bases are
+// fixed, so it's one conditional addition of a precomputed 2^j * base per scalar bit.
+// the accumulator starts from a fixed offset point so it never has to touch the identity,
+// and the value bits are summed up again so the value can be copy constrained to a committed cell
+//
+//  bit | acc_x | acc_y | lambda | inv               | sum       | base_x, base_y, weight (fixed)
+// -----+-------+-------+--------+-------------------+-----------+-------------------------------
+//   b  |  a_x  |  a_y  | slope  | 1 / (base_x - a_x) | s        | P = 2^j * (G or H), 2^j or 0
+//      | a_x'  | a_y'  |        |                   | s + b * w |
+//
+// with b = 1: (a_x', a_y') = (a_x, a_y) + P using the incomplete addition formulas
+// (inv shows a_x != base_x, so lambda is the unique slope), with b = 0 the accumulator is unchanged
+
+#[derive(Clone, Debug)]
+pub struct PallasMulConfig {
+    pub bit: Column<Advice>,
+    pub acc_x: Column<Advice>,
+    pub acc_y: Column<Advice>,
+    pub lambda: Column<Advice>,
+    pub inv: Column<Advice>,
+    pub sum: Column<Advice>,
+    pub base_x: Column<Fixed>,
+    pub base_y: Column<Fixed>,
+    pub weight: Column<Fixed>,
+    pub q_add: Selector,
+}
+
+pub struct PallasMulChip {
+    config: PallasMulConfig,
+    g: pallas::Point,      // base of the value
+    h: pallas::Point,      // base of the blinding
+    offset: pallas::Point, // where the accumulator starts
+}
+
+impl PallasMulChip {
+    pub fn construct(config: PallasMulConfig, g: pallas::Point, h: pallas::Point, offset: pallas::Point) -> Self {
+        PallasMulChip { config, g, h, offset }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<Fp>,
+        bit: Column<Advice>,
+        acc_x: Column<Advice>,
+        acc_y: Column<Advice>,
+        lambda: Column<Advice>,
+        inv: Column<Advice>,
+        sum: Column<Advice>,
+    ) -> PallasMulConfig {
+        let base_x = meta.fixed_column();
+        let base_y = meta.fixed_column();
+        let weight = meta.fixed_column();
+        let q_add = meta.selector();
+
+        meta.create_gate("conditional_add", |meta| {
+            let q = meta.query_selector(q_add);
+            let b = meta.query_advice(bit, Rotation::cur());
+            let a_x = meta.query_advice(acc_x, Rotation::cur());
+            let a_y = meta.query_advice(acc_y, Rotation::cur());
+            let next_x = meta.query_advice(acc_x, Rotation::next());
+            let next_y = meta.query_advice(acc_y, Rotation::next());
+            let lambda = meta.query_advice(lambda, Rotation::cur());
+            let inv = meta.query_advice(inv, Rotation::cur());
+            let s = meta.query_advice(sum, Rotation::cur());
+            let next_s = meta.query_advice(sum, Rotation::next());
+            let p_x = meta.query_fixed(base_x);
+            let p_y = meta.query_fixed(base_y);
+            let w = meta.query_fixed(weight);
+            let one = Expression::Constant(Fp::ONE);
+
+            let sum_x = lambda.clone() * lambda.clone() - a_x.clone() - p_x.clone();
+            let sum_y = lambda.clone() * (a_x.clone() - next_x.clone()) - a_y.clone();
+
+            vec![
+                q.clone() * b.clone() * (one.clone() - b.clone()),
+                q.clone() * b.clone() * (lambda * (p_x.clone() - a_x.clone()) - (p_y - a_y.clone())),
+                q.clone() * b.clone() * ((p_x - a_x.clone()) * inv - one),
+                q.clone() * (next_x - a_x.clone() - b.clone() * (sum_x - a_x)),
+                q.clone() * (next_y - a_y.clone() - b.clone() * (sum_y - a_y)),
+                q * (next_s - s - b * w),
+            ]
+        });
+
+        PallasMulConfig { bit, acc_x, acc_y, lambda, inv, sum, base_x, base_y, weight, q_add }
+    }
+
+    // returns offset + value * G + blinding * H as assigned (x, y)
+    // value is decomposed into value_bits bits which are constrained to add back up to it
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<Fp>,
+        value: &AssignedCell<Fp, Fp>,
+        value_bits: usize,
+        blinding: Value<Fq>,
+    ) -> Result<CellCoords<Fp>, Error> {
+        // (bit, base point, weight) per row, value bits first then the 255 blinding bits
+        let mut rows = Vec::new();
+        let mut base = self.g;
+        for j in 0..value_bits {
+            let bit = value.value().map(|v| Fp::from(((v.to_repr()[j / 8] >> (j % 8)) & 1) as u64));
+            rows.push((bit, base, Fp::from(1u64 << j)));
+            base = base.double();
+        }
+        let mut base = self.h;
+        for j in 0..Fq::NUM_BITS as usize {
+            let bit = blinding.map(|r| Fp::from(((r.to_repr()[j / 8] >> (j % 8)) & 1) as u64));
+            rows.push((bit, base, Fp::ZERO));
+            base = base.double();
+        }
+
+        let offset = point_coordinates(self.offset);
+
+        layouter.assign_region(
+            || "pallas_mul",
+            |mut region| {
+                let mut acc_x = region.assign_advice_from_constant(|| "offset_x", self.config.acc_x, 0, offset.0)?;
+                let mut acc_y = region.assign_advice_from_constant(|| "offset_y", self.config.acc_y, 0, offset.1)?;
+                let mut sum = region.assign_advice_from_constant(|| "sum_start", self.config.sum, 0, Fp::ZERO)?;
+
+                for (row, (bit, base, weight)) in rows.iter().enumerate() {
+                    let (p_x, p_y) = point_coordinates(*base);
+                    self.config.q_add.enable(&mut region, row)?;
+                    region.assign_advice(|| "bit", self.config.bit, row, || *bit)?;
+                    region.assign_fixed(|| "base_x", self.config.base_x, row, || Value::known(p_x))?;
+                    region.assign_fixed(|| "base_y", self.config.base_y, row, || Value::known(p_y))?;
+                    region.assign_fixed(|| "weight", self.config.weight, row, || Value::known(*weight))?;
+
+                    let a = acc_x.value().copied().zip(acc_y.value().copied());
+                    let inv = a.map(|(a_x, _)| (p_x - a_x).invert().unwrap_or(Fp::ZERO));
+                    let lambda = a.zip(inv).map(|((_, a_y), inv)| (p_y - a_y) * inv);
+                    let next = a.zip(lambda).zip(*bit).map(|(((a_x, a_y), lambda), bit)| {
+                        if bit == Fp::ONE {
+                            let x = lambda * lambda - a_x - p_x;
+                            (x, lambda * (a_x - x) - a_y)
+                        } else {
+                            (a_x, a_y)
+                        }
+                    });
+
+                    region.assign_advice(|| "lambda", self.config.lambda, row, || lambda)?;
+                    region.assign_advice(|| "inv", self.config.inv, row, || inv)?;
+                    acc_x = region.assign_advice(|| "acc_x", self.config.acc_x, row + 1, || next.map(|n| n.0))?;
+                    acc_y = region.assign_advice(|| "acc_y", self.config.acc_y, row + 1, || next.map(|n| n.1))?;
+                    let next_sum = sum.value().copied().zip(*bit).map(|(s, b)| s + b * weight);
+                    sum = region.assign_advice(|| "sum", self.config.sum, row + 1, || next_sum)?;
+                }
+
+                // the bits have to be the committed value
+                region.constrain_equal(sum.cell(), value.cell())?;
+                Ok((acc_x, acc_y))
+            },
+        )
+    }
+}
+
+// affine (x, y) of a Pallas point that isn't the identity
+fn point_coordinates(point: pallas::Point) -> (Fp, Fp) {
+    let coordinates = point.to_affine().coordinates().unwrap();
+    (*coordinates.x(), *coordinates.y())
+}
+
+// SNARK circuit verifies that attack coordinates match ship coordinates
+#[derive(Clone)]
+struct BattleshipCircuit {
+    ships: Vec<ShipWitness>, // private ship data (commitments are public inputs)
+    fleet: Vec<u8>,      // public ship lengths, one per ship
+    attack_x: Value<Fp>, // attack x-coordinate
+    attack_y: Value<Fp>, // attack y-coordinate
+    hit: Value<Fp>,      // 1 if hit, 0 if miss
+    grid_size: GridSize, // every ship cell and attack coordinate must be on the board
+}
+
+impl BattleshipCircuit {
+    // circuit without witnesses for keygen, the fleet has to be fixed since it decides how many rows the circuit uses
+    fn empty(grid_size: GridSize, fleet: &[u8]) -> Self {
+        BattleshipCircuit {
+            ships: fleet.iter().map(|_| ShipWitness::unknown()).collect(), // one witness per ship in the fleet
+            fleet: fleet.to_vec(),
+            attack_x: Value::unknown(),
+            attack_y: Value::unknown(),
+            hit: Value::unknown(),
+            grid_size,
+        }
+    }
+}
+
+impl Circuit<Fp> for BattleshipCircuit {
+
+    type Config = (
+        Pow5Config<Fp, 3, 2>,
+        Column<Advice>,        // advice_col
+        Column<Advice>,        // result_col
+        Column<Advice>,        // diff_col
+        Column<Advice>,        // value_inv_col
+        Column<Advice>,        // is_zero_result_col
+        Column<Instance>,
+        IsZeroConfig<Fp>,      // IsZero config
+        Selector,              // q_is_zero selector
+        BoolOpsConfig,         // AND/OR gates for hit aggregation
+        RangeCheckConfig,      // lookup based coordinate range check
+        ShipCellsConfig,       // expands a ship into its cells
+    );
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    // no witnesses are needed for the circuit
+    fn without_witnesses(&self) -> Self {
+        Self {
+            ships: self.ships.iter().map(|_| ShipWitness::unknown()).collect(),
+            fleet: self.fleet.clone(),
+            attack_x: Value::unknown(),
+            attack_y: Value::unknown(),
+            hit: Value::unknown(),
+            grid_size: self.grid_size, // the range table is part of the circuit shape
+        }
+    }
+
+    // configures the circuit with proper constraint gates for hit verification
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+
+        let poseidon_config = configure_poseidon(meta);
+
+        let advice = meta.advice_column();
+        let result = meta.advice_column();
+        let diff_col = meta.advice_column();
+        let value_inv_col = meta.advice_column();
         let is_zero_result_col = meta.advice_column();
         let instance = meta.instance_column();
         let q_is_zero = meta.selector();
@@ -478,6 +1818,36 @@ impl Circuit<Fp> for BattleshipCircuit {
             value_inv_col,
         );
 
+        // ties the coordinate check together: diff = ship - attack and the stored
+        // is_zero result has to be the boolean the IsZero chip computes from diff
+        //
+        // advice (ship) | result (attack) | diff | value_inv | is_zero_result
+        meta.create_gate("coordinate_match", |meta| {
+            let q = meta.query_selector(q_is_zero);
+            let ship = meta.query_advice(advice, Rotation::cur());
+            let attack = meta.query_advice(result, Rotation::cur());
+            let diff = meta.query_advice(diff_col, Rotation::cur());
+            let is_zero = meta.query_advice(is_zero_result_col, Rotation::cur());
+            let one = Expression::Constant(Fp::ONE);
+
+            vec![
+                q.clone() * (diff - (ship - attack)),
+                q.clone() * (is_zero.clone() - is_zero_config.expr()),
+                q * is_zero.clone() * (one - is_zero),
+            ]
+        });
+
+        let bool_ops_config = BoolOpsChip::<Fp>::configure(meta, advice, result, is_zero_result_col);
+        let range_check_config = RangeCheckChip::<Fp>::configure(meta, diff_col);
+        let ship_cells_config = ShipCellsChip::<Fp>::configure(
+            meta,
+            advice,
+            result,
+            diff_col,
+            value_inv_col,
+            is_zero_result_col,
+        );
+
         (
             poseidon_config,
             advice,
@@ -488,6 +1858,9 @@ impl Circuit<Fp> for BattleshipCircuit {
             instance,
             is_zero_config,
             q_is_zero,
+            bool_ops_config,
+            range_check_config,
+            ship_cells_config,
         )
     }
 
@@ -498,196 +1871,171 @@ impl Circuit<Fp> for BattleshipCircuit {
             advice_col,
             result_col,
             diff_col,
-            value_inv_col,
+            _value_inv_col, // owned by the IsZero chip
             is_zero_result_col,
             instance_col,
             is_zero_config,
             q_is_zero,
+            bool_ops_config,
+            range_check_config,
+            ship_cells_config,
         ) = config;
 
-        // prep IsZero chip
+        // prep IsZero and AND/OR chips
         let is_zero_chip = IsZeroChip::construct(is_zero_config.clone());
+        let bool_ops_chip = BoolOpsChip::<Fp>::construct(bool_ops_config);
+        let ship_cells_chip = ShipCellsChip::<Fp>::construct(ship_cells_config);
+        let range_check_chip = RangeCheckChip::<Fp>::construct(range_check_config, self.grid_size);
+        range_check_chip.load_table(layouter.namespace(|| "load_coordinate_table"))?;
+
+        // every (x, y) cell covered by a committed ship, reused by the match checks below
+        let mut ship_cells = Vec::new();
 
         // verify each ship's commitment
 
-         for (i, (ship, published_commitment)) in self.ships.iter()
-            .zip(self.published_commitments.iter())
-            .enumerate()
-        {
-            let (ship_x, ship_y, salt) = ship;
-            
-            // assign ship values to cells first or else poseidon hashing won't work (i think its because of how halo2 works with layouters and regions)
-            let message: [halo2_proofs::circuit::AssignedCell<Fp, Fp>; 3] = layouter.assign_region(
-                || format!("assign_ship_values_{}", i),
-                |mut region| {
-                    let x_cell = region.assign_advice(
-                        || "ship_x",
-                        advice_col,
-                        0,
-                        || *ship_x,
-                    )?;
-                    let y_cell = region.assign_advice(
-                        || "ship_y",
-                        advice_col,
-                        1,
-                        || *ship_y,
-                    )?;
-                    let salt_cell = region.assign_advice(
-                        || "salt",
-                        advice_col,
-                        2,
-                        || *salt,
-                    )?;
-                    Ok([x_cell, y_cell, salt_cell])
-                },
-            )?;
-            
-            // Create Poseidon hasher for this ship
-            let poseidon_chip = Pow5Chip::construct(poseidon_config.clone());
-            let hasher = halo2_gadgets::poseidon::Hash::<Fp, Pow5Chip<Fp, 3, 2>, P128Pow5T3, halo2_gadgets::poseidon::primitives::ConstantLength<3>, 3, 2>::init(
-                poseidon_chip,
-                layouter.namespace(|| format!("poseidon_init_{}", i)),
-            )?;
-            
-            // Hash ship data hash(x, y, salt)
-            let computed_commitment = hasher.hash(
-                layouter.namespace(|| format!("hash_ship_{}", i)),
-                message,
+        for (i, ship) in self.ships.iter().enumerate() {
+            let hashed = hash_ship(
+                &mut layouter,
+                &poseidon_config,
+                advice_col,
+                ship,
+                i,
             )?;
+            // committed length has to match the public fleet spec
+            constrain_ship_length(&mut layouter, &hashed.length, self.fleet[i])?;
+
+            ship_cells.extend(ship_cells_chip.assign(
+                layouter.namespace(|| format!("ship_cells_{}", i)),
+                &hashed.x,
+                &hashed.y,
+                &hashed.orientation,
+                self.fleet[i],
+            )?);
 
             // Verify computed commitment == published commitment
-            layouter.assign_region(
-                || format!("verify_commitment_{}", i),
-                |mut region| {
-                    let published = region.assign_advice(
-                        || "published_commitment",
-                        advice_col,
-                        0,
-                        || *published_commitment,
-                    )?;
-                    
-                    // Constrain equality
-                    region.constrain_equal(computed_commitment.cell(), published.cell())?;
-                    
-                    Ok(())
-                },
+            // the published commitment lives in the instance column so the verifier binds
+            // the proof to the exact hashes published at setup
+            layouter.constrain_instance(
+                hashed.commitment.cell(),
+                instance_col,
+                COMMITMENTS_INSTANCE_OFFSET + i,
             )?;
         }
 
-        // Computes whether any ship matches attack using IsZero constraints
+        // assign the attack coordinates and bind them to the instance column so a proof
+        // only holds for the shot the opponent actually fired
+        let (attack_x_cell, attack_y_cell) = layouter.assign_region(
+            || "assign_attack",
+            |mut region| {
+                let x_cell = region.assign_advice(|| "attack_x", advice_col, 0, || self.attack_x)?;
+                let y_cell = region.assign_advice(|| "attack_y", advice_col, 1, || self.attack_y)?;
+                Ok((x_cell, y_cell))
+            },
+        )?;
+        layouter.constrain_instance(attack_x_cell.cell(), instance_col, ATTACK_X_INSTANCE_ROW)?;
+        layouter.constrain_instance(attack_y_cell.cell(), instance_col, ATTACK_Y_INSTANCE_ROW)?;
+
+        // every committed ship cell and the attack have to be on the board
+        for (i, (ship_x_cell, ship_y_cell)) in ship_cells.iter().enumerate() {
+            range_check_chip.check_x(layouter.namespace(|| format!("range_cell_x_{}", i)), ship_x_cell)?;
+            range_check_chip.check_y(layouter.namespace(|| format!("range_cell_y_{}", i)), ship_y_cell)?;
+        }
+        range_check_chip.check_x(layouter.namespace(|| "range_attack_x"), &attack_x_cell)?;
+        range_check_chip.check_y(layouter.namespace(|| "range_attack_y"), &attack_y_cell)?;
 
+        // Computes whether any ship cell matches attack using IsZero constraints
+        // each check copies the committed ship coordinate and the public attack coordinate
+        // into the row so diff (and therefore the match bit) can't be chosen freely
         let mut ship_match_cells = Vec::new();
-        
-        for (i, (ship_x, ship_y, _salt)) in self.ships.iter().enumerate() {
-            // Checks X coordinate
-            let x_diff = ship_x.zip(self.attack_x).map(|(sx, ax)| sx - ax);
-            
-            let x_is_zero = layouter.assign_region(
-                || format!("check_x_{}", i),
-                |mut region| {
-                    q_is_zero.enable(&mut region, 0)?;
-                    
-                    // Assign difference
-                    region.assign_advice(|| "x_diff", diff_col, 0, || x_diff)?;
-                    
-                    // Assign value_inv via IsZero chip
-                    is_zero_chip.assign(&mut region, 0, x_diff)?;
-                    
-                    // Compute is_zero value for x
-                    let is_zero_value = x_diff.map(|d| {
-                        if d.is_zero_vartime() { Fp::ONE } else { Fp::ZERO }
-                    });
-                    
-                    region.assign_advice(
-                        || "x_is_zero",
-                        is_zero_result_col,
-                        0,
-                        || is_zero_value,
-                    )
-                },
-            )?;
 
-            // Checks Y coordinate
-            let y_diff = ship_y.zip(self.attack_y).map(|(sy, ay)| sy - ay);
-            
-            let y_is_zero = layouter.assign_region(
-                || format!("check_y_{}", i),
-                |mut region| {
-                    q_is_zero.enable(&mut region, 0)?;
-                    region.assign_advice(|| "y_diff", diff_col, 0, || y_diff)?;
-                    is_zero_chip.assign(&mut region, 0, y_diff)?;
-                    
-                    let is_zero_value = y_diff.map(|d| {
-                        if d.is_zero_vartime() { Fp::ONE } else { Fp::ZERO }
-                    });
-                    
-                    region.assign_advice(
-                        || "y_is_zero",
-                        is_zero_result_col,
-                        0,
-                        || is_zero_value,
-                    )
-                },
-            )?;
+        for (i, (ship_x_cell, ship_y_cell)) in ship_cells.iter().enumerate() {
+            // Checks X and Y coordinates
+            let mut axis_is_zero = Vec::new();
+            for (axis, ship_cell, attack_cell) in [
+                ("x", ship_x_cell, &attack_x_cell),
+                ("y", ship_y_cell, &attack_y_cell),
+            ] {
+                let is_zero_cell = layouter.assign_region(
+                    || format!("check_{}_{}", axis, i),
+                    |mut region| {
+                        q_is_zero.enable(&mut region, 0)?;
+
+                        ship_cell.copy_advice(|| "ship_coord", &mut region, advice_col, 0)?;
+                        attack_cell.copy_advice(|| "attack_coord", &mut region, result_col, 0)?;
+
+                        // Assign difference
+                        let diff = ship_cell.value().zip(attack_cell.value()).map(|(s, a)| *s - *a);
+                        region.assign_advice(|| "diff", diff_col, 0, || diff)?;
+
+                        // Assign value_inv via IsZero chip
+                        is_zero_chip.assign(&mut region, 0, diff)?;
+
+                        // is_zero value, pinned to the chip's expression by the coordinate_match gate
+                        let is_zero_value = diff.map(|d| {
+                            if d.is_zero_vartime() { Fp::ONE } else { Fp::ZERO }
+                        });
+
+                        region.assign_advice(
+                            || "is_zero",
+                            is_zero_result_col,
+                            0,
+                            || is_zero_value,
+                        )
+                    },
+                )?;
+                axis_is_zero.push(is_zero_cell);
+            }
+            let (x_is_zero, y_is_zero) = (&axis_is_zero[0], &axis_is_zero[1]);
 
-            // ship_matches = x_is_zero AND y_is_zero
-            let ship_matches = layouter.assign_region(
-                || format!("combine_xy_{}", i),
-                |mut region| {
-                    let x_val = x_is_zero.value().copied();
-                    let y_val = y_is_zero.value().copied();
-                    let and_result = x_val.zip(y_val).map(|(x, y)| x * y);
-                    
-                    region.assign_advice(|| "ship_matches", result_col, 0, || and_result)
-                },
+            // cell_matches = x_is_zero AND y_is_zero
+            let ship_matches = bool_ops_chip.and(
+                layouter.namespace(|| format!("combine_xy_{}", i)),
+                x_is_zero,
+                y_is_zero,
             )?;
 
             ship_match_cells.push(ship_matches);
         }
 
-        // OR all ship matches: hit = match_0 OR match_1 OR ... OR match_n
-        // Uses boolean OR formula: a OR b = a + b - a*b
-        let computed_hit = layouter.assign_region(
-            || "compute_overall_hit",
-            |mut region| {
-                if ship_match_cells.is_empty() {
-                    // No ships:  return 0 (miss)
-                    region.assign_advice(
-                        || "computed_hit_empty",
-                        result_col,
-                        0,
-                        || Value::known(Fp::ZERO),
-                    )
-                } else {
-                    // Compute OR of all ship matches
-                    let mut acc = ship_match_cells[0].value().copied();
-                    for cell in ship_match_cells.iter().skip(1) {
-                        let match_val = cell.value().copied();
-                        acc = acc.zip(match_val).map(|(a, m)| a + m - (a * m));
-                    }
-                    
-                    region.assign_advice(
-                        || "computed_hit",
-                        result_col,
-                        0,
-                        || acc,
-                    )
+        // OR all cell matches: hit = match_0 OR match_1 OR ... OR match_n
+        // Uses boolean OR formula: a OR b = a + b - a*b, chaining the accumulator through copy constraints
+        let computed_hit = match ship_match_cells.split_first() {
+            None => {
+                // No ships: fixed 0 (miss)
+                layouter.assign_region(
+                    || "compute_overall_hit",
+                    |mut region| {
+                        region.assign_advice_from_constant(
+                            || "computed_hit_empty",
+                            result_col,
+                            0,
+                            Fp::ZERO,
+                        )
+                    },
+                )?
+            }
+            Some((first, rest)) => {
+                let mut acc = first.clone();
+                for (i, cell) in rest.iter().enumerate() {
+                    acc = bool_ops_chip.or(
+                        layouter.namespace(|| format!("compute_overall_hit_{}", i)),
+                        &acc,
+                        cell,
+                    )?;
                 }
-            },
-        )?;
+                acc
+            }
+        };
 
-        // verify hit and expose to instance column in single region
+        // verify the claimed hit against the computed one and expose it to the instance column
         let hit_cell = layouter.assign_region(
             || "verify_and_expose_hit",
             |mut region| {
-                // copy the computed_hit cell
-                let computed_value = computed_hit.value().copied();
-                
-                let computed_cell = region.assign_advice(
+                let computed_cell = computed_hit.copy_advice(
                     || "computed_hit_copy",
+                    &mut region,
                     result_col,
                     0,
-                    || computed_value,
                 )?;
 
                 let claimed_cell = region.assign_advice(
@@ -702,25 +2050,1023 @@ impl Circuit<Fp> for BattleshipCircuit {
             },
         )?;
 
-        layouter.constrain_instance(hit_cell.cell(), instance_col, 0)?;
+        layouter.constrain_instance(hit_cell.cell(), instance_col, HIT_INSTANCE_ROW)?;
 
         Ok(())
 
     }
 }
 
-// generates the proof
-fn generate_proof(
-    params: &Params<EqAffine>,
-    pk: &ProvingKey<EqAffine>,
-    circuit: BattleshipCircuit,
-    public_inputs: &[Fp],
-) -> Vec<u8> {
-    let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<EqAffine>>::init(vec![]);
-    
-    create_proof(
-        params,
-        pk,
+// SNARK circuit proven once after placement: every committed ship is on the board,
+// no two ships overlap and (depending on the rule) no two ships touch,
+// without revealing where any of them are
+#[derive(Clone)]
+struct FleetSetupCircuit {
+    ships: Vec<ShipWitness>, // private ship data (commitments are public inputs)
+    fleet: Vec<u8>,          // public ship lengths, one per ship
+    grid_size: GridSize,
+    adjacency: AdjacencyRule, // decides which pair check is enabled, so it is part of the keys
+}
+
+impl FleetSetupCircuit {
+    fn empty(grid_size: GridSize, fleet: &[u8], adjacency: AdjacencyRule) -> Self {
+        FleetSetupCircuit {
+            ships: fleet.iter().map(|_| ShipWitness::unknown()).collect(),
+            fleet: fleet.to_vec(),
+            grid_size,
+            adjacency,
+        }
+    }
+}
+
+impl Circuit<Fp> for FleetSetupCircuit {
+
+    type Config = (
+        Pow5Config<Fp, 3, 2>,
+        Column<Advice>,        // advice_col
+        [Column<Advice>; 4],   // (x_a, y_a, x_b, y_b) for the pair checks
+        Column<Advice>,        // inverse used by the pair checks
+        Column<Instance>,
+        Selector,              // q_no_overlap selector
+        Selector,              // q_no_touch selector
+        RangeCheckConfig,      // lookup based coordinate range check
+        ShipCellsConfig,       // expands a ship into its cells
+    );
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            ships: self.ships.iter().map(|_| ShipWitness::unknown()).collect(),
+            fleet: self.fleet.clone(),
+            grid_size: self.grid_size,
+            adjacency: self.adjacency,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let poseidon_config = configure_poseidon(meta);
+
+        let advice = meta.advice_column();
+        let pair = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let diff_inv = meta.advice_column();
+        let instance = meta.instance_column();
+        let q_no_overlap = meta.selector();
+        let q_no_touch = meta.selector();
+
+        meta.enable_equality(advice);
+        for col in pair {
+            meta.enable_equality(col);
+        }
+        meta.enable_equality(instance);
+
+        // pair checks run on one cell from each of two different ships, with dx = x_a - x_b
+        // and dy = y_a - y_b. a value is shown non-zero by giving its inverse (0 has none)
+        // every coordinate is range checked to be < width or height <= 256 so |dx|, |dy| < 256
+        // and packing the two parts below can't wrap around the field
+        //
+        // x_a | y_a | x_b | y_b | inv
+        //
+        // no_overlap: dx + 2^16 * dy != 0                       (not the same cell)
+        // no_touch:   (dx^3 - dx) + 2^32 * (dy^3 - dy) != 0     (d^3 - d = 0 only for d in {-1, 0, 1},
+        //                                                        so the cells aren't neighbours, diagonals included)
+        let pair_deltas = |meta: &mut VirtualCells<'_, Fp>| {
+            let x_a = meta.query_advice(pair[0], Rotation::cur());
+            let y_a = meta.query_advice(pair[1], Rotation::cur());
+            let x_b = meta.query_advice(pair[2], Rotation::cur());
+            let y_b = meta.query_advice(pair[3], Rotation::cur());
+            let inv = meta.query_advice(diff_inv, Rotation::cur());
+            (x_a - x_b, y_a - y_b, inv)
+        };
+
+        meta.create_gate("no_overlap", |meta| {
+            let q = meta.query_selector(q_no_overlap);
+            let (dx, dy, inv) = pair_deltas(meta);
+            let one = Expression::Constant(Fp::ONE);
+
+            let packed = dx + Expression::Constant(Fp::from(1 << 16)) * dy;
+            vec![q * (packed * inv - one)]
+        });
+
+        meta.create_gate("no_touch", |meta| {
+            let q = meta.query_selector(q_no_touch);
+            let (dx, dy, inv) = pair_deltas(meta);
+            let one = Expression::Constant(Fp::ONE);
+
+            let near_x = dx.clone() * dx.clone() * dx.clone() - dx;
+            let near_y = dy.clone() * dy.clone() * dy.clone() - dy;
+            let packed = near_x + Expression::Constant(Fp::from(1 << 32)) * near_y;
+            vec![q * (packed * inv - one)]
+        });
+
+        let range_check_config = RangeCheckChip::<Fp>::configure(meta, advice);
+        let ship_cells_config = ShipCellsChip::<Fp>::configure(meta, pair[0], pair[1], pair[2], pair[3], advice);
+
+        (
+            poseidon_config,
+            advice,
+            pair,
+            diff_inv,
+            instance,
+            q_no_overlap,
+            q_no_touch,
+            range_check_config,
+            ship_cells_config,
+        )
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+
+        let (
+            poseidon_config,
+            advice_col,
+            pair_cols,
+            diff_inv_col,
+            instance_col,
+            q_no_overlap,
+            q_no_touch,
+            range_check_config,
+            ship_cells_config,
+        ) = config;
+
+        let ship_cells_chip = ShipCellsChip::<Fp>::construct(ship_cells_config);
+        let range_check_chip = RangeCheckChip::<Fp>::construct(range_check_config, self.grid_size);
+        range_check_chip.load_table(layouter.namespace(|| "load_coordinate_table"))?;
+
+        // hash every ship, bind it to its published commitment and check all its cells are on the board
+        let mut fleet_cells = Vec::new();
+        for (i, ship) in self.ships.iter().enumerate() {
+            let hashed = hash_ship(
+                &mut layouter,
+                &poseidon_config,
+                advice_col,
+                ship,
+                i,
+            )?;
+            layouter.constrain_instance(hashed.commitment.cell(), instance_col, i)?;
+            constrain_ship_length(&mut layouter, &hashed.length, self.fleet[i])?;
+
+            let cells = ship_cells_chip.assign(
+                layouter.namespace(|| format!("ship_cells_{}", i)),
+                &hashed.x,
+                &hashed.y,
+                &hashed.orientation,
+                self.fleet[i],
+            )?;
+            for (k, (cell_x, cell_y)) in cells.iter().enumerate() {
+                range_check_chip.check_x(layouter.namespace(|| format!("range_cell_x_{}_{}", i, k)), cell_x)?;
+                range_check_chip.check_y(layouter.namespace(|| format!("range_cell_y_{}_{}", i, k)), cell_y)?;
+            }
+
+            fleet_cells.push(cells);
+        }
+
+        // no touching implies no overlap, so only one of the two checks is needed per pair
+        let pair_selector = match self.adjacency {
+            AdjacencyRule::Allowed => q_no_overlap,
+            AdjacencyRule::Forbidden => q_no_touch,
+        };
+
+        // every cell of a ship against every cell of every later ship
+        for i in 0..fleet_cells.len() {
+            for j in (i + 1)..fleet_cells.len() {
+                for (a, (x_a, y_a)) in fleet_cells[i].iter().enumerate() {
+                    for (b, (x_b, y_b)) in fleet_cells[j].iter().enumerate() {
+                        layouter.assign_region(
+                            || format!("separate_{}_{}_{}_{}", i, a, j, b),
+                            |mut region| {
+                                pair_selector.enable(&mut region, 0)?;
+                                x_a.copy_advice(|| "x_a", &mut region, pair_cols[0], 0)?;
+                                y_a.copy_advice(|| "y_a", &mut region, pair_cols[1], 0)?;
+                                x_b.copy_advice(|| "x_b", &mut region, pair_cols[2], 0)?;
+                                y_b.copy_advice(|| "y_b", &mut region, pair_cols[3], 0)?;
+
+                                let dx = x_a.value().zip(x_b.value()).map(|(a, b)| *a - *b);
+                                let dy = y_a.value().zip(y_b.value()).map(|(a, b)| *a - *b);
+                                let packed = dx.zip(dy).map(|(dx, dy)| match self.adjacency {
+                                    AdjacencyRule::Allowed => dx + Fp::from(1 << 16) * dy,
+                                    AdjacencyRule::Forbidden => {
+                                        (dx * dx * dx - dx) + Fp::from(1 << 32) * (dy * dy * dy - dy)
+                                    }
+                                });
+                                let inv = packed.map(|p| p.invert().unwrap_or(Fp::ZERO));
+                                region.assign_advice(|| "inv", diff_inv_col, 0, || inv)?;
+                                Ok(())
+                            },
+                        )?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// SNARK circuit for the board commitment mode: opens the attacked cell of the committed board
+// with a Merkle path, so a shot costs log2(board cells) hashes no matter how many ships there are
+#[derive(Clone)]
+struct MerkleShotCircuit {
+    occupied: Value<Fp>,        // occupancy bit of the attacked cell, this is the hit bit
+    salt: Value<Fp>,            // salt of the attacked cell
+    siblings: Vec<Value<Fp>>,   // sibling hashes from the leaf up to the root
+    index_bits: Vec<Value<Fp>>, // attacked cell index in binary, leaf level first
+    attack_x: Value<Fp>,        // attack x-coordinate
+    attack_y: Value<Fp>,        // attack y-coordinate
+    grid_size: GridSize,        // attack coordinates must be on the board
+}
+
+impl MerkleShotCircuit {
+    fn empty(grid_size: GridSize) -> Self {
+        let depth = board_depth(grid_size);
+        MerkleShotCircuit {
+            occupied: Value::unknown(),
+            salt: Value::unknown(),
+            siblings: vec![Value::unknown(); depth],
+            index_bits: vec![Value::unknown(); depth],
+            attack_x: Value::unknown(),
+            attack_y: Value::unknown(),
+            grid_size,
+        }
+    }
+}
+
+impl Circuit<Fp> for MerkleShotCircuit {
+
+    type Config = (
+        Pow5Config<Fp, 3, 2>,
+        [Column<Advice>; 7],   // (bit, cur, sibling, left, right, acc_in, acc_out) per tree level
+        Column<Fixed>,         // 2^level weight for the index accumulator
+        Column<Instance>,
+        Selector,              // q_level selector
+        Selector,              // q_index selector
+        Selector,              // q_occupied selector
+        RangeCheckConfig,      // lookup based coordinate range check
+    );
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            occupied: Value::unknown(),
+            salt: Value::unknown(),
+            siblings: self.siblings.iter().map(|_| Value::unknown()).collect(),
+            index_bits: self.index_bits.iter().map(|_| Value::unknown()).collect(),
+            attack_x: Value::unknown(),
+            attack_y: Value::unknown(),
+            grid_size: self.grid_size,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let poseidon_config = configure_poseidon(meta);
+
+        let cols = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let weight = meta.fixed_column();
+        let instance = meta.instance_column();
+        let q_level = meta.selector();
+        let q_index = meta.selector();
+        let q_occupied = meta.selector();
+
+        for col in cols {
+            meta.enable_equality(col);
+        }
+        meta.enable_equality(instance);
+
+        // one row per tree level, the bit says whether the current node is the right child
+        //
+        // bit | cur | sibling | left                      | right                     | acc_in | acc_out
+        //  b  |  c  |    s    | c + b * (s - c)           | s + b * (c - s)           |   a    | a + b * 2^level
+        meta.create_gate("merkle_level", |meta| {
+            let q = meta.query_selector(q_level);
+            let bit = meta.query_advice(cols[0], Rotation::cur());
+            let cur = meta.query_advice(cols[1], Rotation::cur());
+            let sibling = meta.query_advice(cols[2], Rotation::cur());
+            let left = meta.query_advice(cols[3], Rotation::cur());
+            let right = meta.query_advice(cols[4], Rotation::cur());
+            let acc_in = meta.query_advice(cols[5], Rotation::cur());
+            let acc_out = meta.query_advice(cols[6], Rotation::cur());
+            let weight = meta.query_fixed(weight);
+            let one = Expression::Constant(Fp::ONE);
+
+            vec![
+                q.clone() * bit.clone() * (one - bit.clone()),
+                q.clone() * (left - (cur.clone() + bit.clone() * (sibling.clone() - cur.clone()))),
+                q.clone() * (right - (sibling.clone() + bit.clone() * (cur - sibling))),
+                q * (acc_out - (acc_in + bit * weight)),
+            ]
+        });
+
+        // the cell index the path opens has to be the attacked cell
+        //
+        // attack_x | attack_y | width (constant) | index
+        meta.create_gate("cell_index", |meta| {
+            let q = meta.query_selector(q_index);
+            let x = meta.query_advice(cols[0], Rotation::cur());
+            let y = meta.query_advice(cols[1], Rotation::cur());
+            let size = meta.query_advice(cols[2], Rotation::cur());
+            let index = meta.query_advice(cols[3], Rotation::cur());
+
+            vec![q * (index - (x + size * y))]
+        });
+
+        // the opened leaf is Poseidon(occupied, salt) with occupied a bit
+        meta.create_gate("occupied_bool", |meta| {
+            let q = meta.query_selector(q_occupied);
+            let occupied = meta.query_advice(cols[0], Rotation::cur());
+            let one = Expression::Constant(Fp::ONE);
+
+            vec![q * occupied.clone() * (one - occupied)]
+        });
+
+        let range_check_config = RangeCheckChip::<Fp>::configure(meta, cols[4]);
+
+        (
+            poseidon_config,
+            cols,
+            weight,
+            instance,
+            q_level,
+            q_index,
+            q_occupied,
+            range_check_config,
+        )
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+
+        let (
+            poseidon_config,
+            cols,
+            weight_col,
+            instance_col,
+            q_level,
+            q_index,
+            q_occupied,
+            range_check_config,
+        ) = config;
+
+        let range_check_chip = RangeCheckChip::<Fp>::construct(range_check_config, self.grid_size);
+        range_check_chip.load_table(layouter.namespace(|| "load_coordinate_table"))?;
+
+        // attacked cell: public coordinates and the row major index they point at
+        let (attack_x_cell, attack_y_cell, index_cell) = layouter.assign_region(
+            || "assign_attack",
+            |mut region| {
+                q_index.enable(&mut region, 0)?;
+                let x_cell = region.assign_advice(|| "attack_x", cols[0], 0, || self.attack_x)?;
+                let y_cell = region.assign_advice(|| "attack_y", cols[1], 0, || self.attack_y)?;
+                let size = Fp::from(self.grid_size.width as u64);
+                region.assign_advice_from_constant(|| "width", cols[2], 0, size)?;
+                let index = self.attack_x.zip(self.attack_y).map(|(x, y)| x + size * y);
+                let index_cell = region.assign_advice(|| "index", cols[3], 0, || index)?;
+                Ok((x_cell, y_cell, index_cell))
+            },
+        )?;
+        layouter.constrain_instance(attack_x_cell.cell(), instance_col, ATTACK_X_INSTANCE_ROW)?;
+        layouter.constrain_instance(attack_y_cell.cell(), instance_col, ATTACK_Y_INSTANCE_ROW)?;
+
+        // without this a coordinate past the edge could alias another row's cell
+        range_check_chip.check_x(layouter.namespace(|| "range_attack_x"), &attack_x_cell)?;
+        range_check_chip.check_y(layouter.namespace(|| "range_attack_y"), &attack_y_cell)?;
+
+        // leaf = Poseidon(occupied, salt), the occupancy bit is the public hit bit
+        let leaf_message = layouter.assign_region(
+            || "assign_leaf",
+            |mut region| {
+                q_occupied.enable(&mut region, 0)?;
+                let occupied = region.assign_advice(|| "occupied", cols[0], 0, || self.occupied)?;
+                let salt = region.assign_advice(|| "salt", cols[1], 0, || self.salt)?;
+                Ok([occupied, salt])
+            },
+        )?;
+        layouter.constrain_instance(leaf_message[0].cell(), instance_col, HIT_INSTANCE_ROW)?;
+        let mut cur = poseidon_hash_cells(&mut layouter, &poseidon_config, leaf_message, "leaf")?;
+
+        // walk up the tree, accumulating the index bits on the way
+        let mut acc: Option<AssignedCell<Fp, Fp>> = None;
+        for (level, (sibling, bit)) in self.siblings.iter().zip(self.index_bits.iter()).enumerate() {
+            let (left, right, acc_out) = layouter.assign_region(
+                || format!("merkle_level_{}", level),
+                |mut region| {
+                    q_level.enable(&mut region, 0)?;
+                    let weight = Fp::from(1u64 << level);
+                    region.assign_fixed(|| "weight", weight_col, 0, || Value::known(weight))?;
+
+                    region.assign_advice(|| "bit", cols[0], 0, || *bit)?;
+                    cur.copy_advice(|| "cur", &mut region, cols[1], 0)?;
+                    region.assign_advice(|| "sibling", cols[2], 0, || *sibling)?;
+
+                    let cur_value = cur.value().copied();
+                    let left = cur_value.zip(*sibling).zip(*bit).map(|((c, s), b)| c + b * (s - c));
+                    let right = cur_value.zip(*sibling).zip(*bit).map(|((c, s), b)| s + b * (c - s));
+                    let left = region.assign_advice(|| "left", cols[3], 0, || left)?;
+                    let right = region.assign_advice(|| "right", cols[4], 0, || right)?;
+
+                    let acc_in = match &acc {
+                        Some(acc) => acc.copy_advice(|| "acc_in", &mut region, cols[5], 0)?,
+                        None => region.assign_advice_from_constant(|| "acc_in", cols[5], 0, Fp::ZERO)?,
+                    };
+                    let acc_out = acc_in.value().zip(*bit).map(|(a, b)| *a + b * weight);
+                    let acc_out = region.assign_advice(|| "acc_out", cols[6], 0, || acc_out)?;
+
+                    Ok((left, right, acc_out))
+                },
+            )?;
+
+            cur = poseidon_hash_cells(
+                &mut layouter,
+                &poseidon_config,
+                [left, right],
+                &format!("node_{}", level),
+            )?;
+            acc = Some(acc_out);
+        }
+
+        // the root has to be the one published at setup and the path has to lead to the attacked cell
+        layouter.constrain_instance(cur.cell(), instance_col, BOARD_ROOT_INSTANCE_ROW)?;
+        layouter.assign_region(
+            || "index_matches_path",
+            |mut region| match &acc {
+                Some(acc) => region.constrain_equal(acc.cell(), index_cell.cell()),
+                None => region.constrain_constant(index_cell.cell(), Fp::ZERO),
+            },
+        )?;
+
+        Ok(())
+    }
+}
+
+// SNARK circuit for the board commitment mode setup: the published root is a Poseidon Merkle tree
+// over salted occupancy bits (same layout as BoardCommitment) and exactly as many cells are occupied
+// as the fleet has ship cells. without it a board could be committed empty and every shot answered
+// with a valid miss. the ship shapes are only checked by the audit once the board is revealed
+#[derive(Clone)]
+struct BoardSetupCircuit {
+    occupied: Vec<Value<Fp>>, // occupancy bit of every cell, row major
+    salts: Vec<Value<Fp>>,    // salt of every cell
+    grid_size: GridSize,
+    ship_cells: usize, // public number of ship cells in the fleet, fixed by the rules
+}
+
+impl BoardSetupCircuit {
+    fn empty(grid_size: GridSize, fleet: &[u8]) -> Self {
+        BoardSetupCircuit {
+            occupied: vec![Value::unknown(); grid_size.cells()],
+            salts: vec![Value::unknown(); grid_size.cells()],
+            grid_size,
+            ship_cells: fleet_cells(fleet),
+        }
+    }
+}
+
+impl Circuit<Fp> for BoardSetupCircuit {
+
+    type Config = (
+        Pow5Config<Fp, 3, 2>,
+        [Column<Advice>; 3], // (occupied, salt, count) per cell
+        Column<Instance>,
+        Selector,            // q_cell selector
+    );
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            occupied: self.occupied.iter().map(|_| Value::unknown()).collect(),
+            salts: self.salts.iter().map(|_| Value::unknown()).collect(),
+            grid_size: self.grid_size,
+            ship_cells: self.ship_cells,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let poseidon_config = configure_poseidon(meta);
+
+        let cols = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+        let instance = meta.instance_column();
+        let q_cell = meta.selector();
+
+        for col in cols {
+            meta.enable_equality(col);
+        }
+        meta.enable_equality(instance);
+
+        // one row per cell after a zero row, the count adds up the occupancy bits
+        //
+        // occupied | salt | count
+        //    o     |  s   | count_prev + o
+        meta.create_gate("cell_count", |meta| {
+            let q = meta.query_selector(q_cell);
+            let occupied = meta.query_advice(cols[0], Rotation::cur());
+            let count = meta.query_advice(cols[2], Rotation::cur());
+            let count_prev = meta.query_advice(cols[2], Rotation::prev());
+            let one = Expression::Constant(Fp::ONE);
+
+            vec![
+                q.clone() * occupied.clone() * (one - occupied.clone()),
+                q * (count - (count_prev + occupied)),
+            ]
+        });
+
+        (poseidon_config, cols, instance, q_cell)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+
+        let (poseidon_config, cols, instance_col, q_cell) = config;
+
+        // every cell's (occupied, salt) and the running count of occupied cells
+        let (cells, count) = layouter.assign_region(
+            || "assign_cells",
+            |mut region| {
+                let mut count = region.assign_advice_from_constant(|| "count", cols[2], 0, Fp::ZERO)?;
+                let mut cells = Vec::new();
+                for (i, (occupied, salt)) in self.occupied.iter().zip(self.salts.iter()).enumerate() {
+                    q_cell.enable(&mut region, i + 1)?;
+                    let occupied_cell = region.assign_advice(|| "occupied", cols[0], i + 1, || *occupied)?;
+                    let salt_cell = region.assign_advice(|| "salt", cols[1], i + 1, || *salt)?;
+                    let next = count.value().copied().zip(*occupied).map(|(c, o)| c + o);
+                    count = region.assign_advice(|| "count", cols[2], i + 1, || next)?;
+                    cells.push([occupied_cell, salt_cell]);
+                }
+                Ok((cells, count))
+            },
+        )?;
+
+        // a board with fewer (or more) ship cells than the fleet can't be committed
+        layouter.assign_region(
+            || "count_matches_fleet",
+            |mut region| region.constrain_constant(count.cell(), Fp::from(self.ship_cells as u64)),
+        )?;
+
+        // leaves are Poseidon(occupied, salt)
+        let mut level: Vec<Option<AssignedCell<Fp, Fp>>> = Vec::new();
+        for (i, message) in cells.into_iter().enumerate() {
+            level.push(Some(poseidon_hash_cells(&mut layouter, &poseidon_config, message, &format!("leaf_{}", i))?));
+        }
+        level.resize(1 << board_depth(self.grid_size), None);
+
+        // padding subtrees are the same constant at every level, they don't need hashing in-circuit.
+        // None marks a node whose leaves are all padding
+        let mut padding = poseidon_pair(Fp::ZERO, Fp::ZERO);
+        let mut depth = 0;
+        while level.len() > 1 {
+            let mut next = Vec::new();
+            for (i, pair) in level.chunks(2).enumerate() {
+                let node = match pair {
+                    [None, None] => None,
+                    [left, right] => {
+                        let message = layouter.assign_region(
+                            || format!("assign_node_{}_{}", depth, i),
+                            |mut region| {
+                                let mut child = |child: &Option<AssignedCell<Fp, Fp>>, col: Column<Advice>| match child {
+                                    Some(cell) => cell.copy_advice(|| "child", &mut region, col, 0),
+                                    None => region.assign_advice_from_constant(|| "padding", col, 0, padding),
+                                };
+                                Ok([child(left, cols[0])?, child(right, cols[1])?])
+                            },
+                        )?;
+                        Some(poseidon_hash_cells(&mut layouter, &poseidon_config, message, &format!("node_{}_{}", depth, i))?)
+                    }
+                    _ => unreachable!("levels have an even number of nodes"),
+                };
+                next.push(node);
+            }
+            padding = poseidon_pair(padding, padding);
+            level = next;
+            depth += 1;
+        }
+
+        // the board has at least one cell so the root is never padding
+        let root = level[0].as_ref().expect("root covers a real cell");
+        layouter.constrain_instance(root.cell(), instance_col, BOARD_SETUP_ROOT_INSTANCE_ROW)
+    }
+}
+
+// SNARK circuit proving one committed ship is fully sunk: every cell of it is in the public
+// list of confirmed hits on that board. only the sunk ship's commitment is opened in-circuit,
+// so nothing is said about where the other ships are
+#[derive(Clone)]
+struct SunkShipCircuit {
+    ship: ShipWitness, // private data of the sunk ship (its commitment is a public input)
+    length: u8,        // public length of the sunk ship, this is its type
+    max_hits: usize,   // size of the public hit list, unused slots are padded with an off board cell
+}
+
+impl SunkShipCircuit {
+    fn empty(length: u8, fleet: &[u8]) -> Self {
+        SunkShipCircuit {
+            ship: ShipWitness::unknown(),
+            length,
+            max_hits: fleet_cells(fleet),
+        }
+    }
+}
+
+impl Circuit<Fp> for SunkShipCircuit {
+
+    type Config = (
+        Pow5Config<Fp, 3, 2>,
+        Column<Advice>,        // advice_col
+        Column<Instance>,
+        ShipCellsConfig,       // expands the ship into its cells
+        HitListConfig,         // running product of a cell against the hit list
+    );
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            ship: ShipWitness::unknown(),
+            length: self.length,
+            max_hits: self.max_hits,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let poseidon_config = configure_poseidon(meta);
+
+        let advice = meta.advice_column();
+        let cols = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let instance = meta.instance_column();
+
+        meta.enable_equality(advice);
+        for col in cols {
+            meta.enable_equality(col);
+        }
+        meta.enable_equality(instance);
+
+        let ship_cells_config = ShipCellsChip::<Fp>::configure(meta, cols[0], cols[1], cols[2], cols[3], cols[4]);
+        let hit_list_config = HitListChip::<Fp>::configure(meta, cols[0], cols[1], cols[2], cols[3], cols[4]);
+
+        (poseidon_config, advice, instance, ship_cells_config, hit_list_config)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+
+        let (poseidon_config, advice_col, instance_col, ship_cells_config, hit_list_config) = config;
+
+        let ship_cells_chip = ShipCellsChip::<Fp>::construct(ship_cells_config);
+        let hit_list_chip = HitListChip::<Fp>::construct(hit_list_config);
+
+        // open the sunk ship against its published commitment and pin its type
+        let hashed = hash_ship(&mut layouter, &poseidon_config, advice_col, &self.ship, 0)?;
+        layouter.constrain_instance(hashed.commitment.cell(), instance_col, SUNK_COMMITMENT_INSTANCE_ROW)?;
+        constrain_ship_length(&mut layouter, &hashed.length, self.length)?;
+
+        let cells = ship_cells_chip.assign(
+            layouter.namespace(|| "sunk_ship_cells"),
+            &hashed.x,
+            &hashed.y,
+            &hashed.orientation,
+            self.length,
+        )?;
+
+        let hits = hit_list_chip.load_hits(
+            layouter.namespace(|| "hit_list"),
+            instance_col,
+            SUNK_HITS_INSTANCE_OFFSET,
+            self.max_hits,
+        )?;
+
+        // every cell of the ship has to be one of the hits
+        for (k, cell) in cells.iter().enumerate() {
+            let product = hit_list_chip.product(layouter.namespace(|| format!("cell_{}_hit", k)), cell, &hits)?;
+            layouter.assign_region(
+                || format!("cell_{}_is_hit", k),
+                |mut region| region.constrain_constant(product.cell(), Fp::ZERO),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+// SNARK circuit for the state of a whole fleet against the public list of confirmed hits:
+// proves either that every committed ship cell has been hit (game over) or that at least
+// one hasn't (not sunk yet). the claim is a public bit so one set of keys covers both
+#[derive(Clone)]
+struct FleetStatusCircuit {
+    ships: Vec<ShipWitness>, // private ship data (commitments are public inputs)
+    fleet: Vec<u8>,          // public ship lengths, one per ship
+    max_hits: usize,         // size of the public hit list, unused slots are padded with an off board cell
+    all_sunk: Value<Fp>,     // claimed status, 1 if every ship is sunk
+}
+
+impl FleetStatusCircuit {
+    fn empty(fleet: &[u8]) -> Self {
+        FleetStatusCircuit {
+            ships: fleet.iter().map(|_| ShipWitness::unknown()).collect(),
+            fleet: fleet.to_vec(),
+            max_hits: fleet_cells(fleet),
+            all_sunk: Value::unknown(),
+        }
+    }
+}
+
+impl Circuit<Fp> for FleetStatusCircuit {
+
+    type Config = (
+        Pow5Config<Fp, 3, 2>,
+        Column<Advice>,        // advice_col
+        [Column<Advice>; 5],   // ship cells / hit list / is_zero columns
+        Column<Instance>,
+        IsZeroConfig<Fp>,      // is the running product zero, i.e. was the cell hit
+        Selector,              // q_cell_hit selector
+        BoolOpsConfig,         // AND of every cell hit bit
+        ShipCellsConfig,       // expands a ship into its cells
+        HitListConfig,         // running product of a cell against the hit list
+    );
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            ships: self.ships.iter().map(|_| ShipWitness::unknown()).collect(),
+            fleet: self.fleet.clone(),
+            max_hits: self.max_hits,
+            all_sunk: Value::unknown(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let poseidon_config = configure_poseidon(meta);
+
+        let advice = meta.advice_column();
+        let cols = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let instance = meta.instance_column();
+        let q_cell_hit = meta.selector();
+
+        meta.enable_equality(advice);
+        for col in cols {
+            meta.enable_equality(col);
+        }
+        meta.enable_equality(instance);
+
+        let is_zero_config = IsZeroChip::<Fp>::configure(
+            meta,
+            |meta| meta.query_selector(q_cell_hit),
+            |meta| meta.query_advice(cols[0], Rotation::cur()),
+            cols[1],
+        );
+
+        // the stored hit bit has to be the boolean the IsZero chip computes from the product
+        //
+        // product | value_inv | is_hit
+        meta.create_gate("cell_hit", |meta| {
+            let q = meta.query_selector(q_cell_hit);
+            let is_hit = meta.query_advice(cols[2], Rotation::cur());
+            vec![q * (is_hit - is_zero_config.expr())]
+        });
+
+        let bool_ops_config = BoolOpsChip::<Fp>::configure(meta, cols[0], cols[1], cols[2]);
+        let ship_cells_config = ShipCellsChip::<Fp>::configure(meta, cols[0], cols[1], cols[2], cols[3], cols[4]);
+        let hit_list_config = HitListChip::<Fp>::configure(meta, cols[0], cols[1], cols[2], cols[3], cols[4]);
+
+        (
+            poseidon_config,
+            advice,
+            cols,
+            instance,
+            is_zero_config,
+            q_cell_hit,
+            bool_ops_config,
+            ship_cells_config,
+            hit_list_config,
+        )
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+
+        let (
+            poseidon_config,
+            advice_col,
+            cols,
+            instance_col,
+            is_zero_config,
+            q_cell_hit,
+            bool_ops_config,
+            ship_cells_config,
+            hit_list_config,
+        ) = config;
+
+        let is_zero_chip = IsZeroChip::construct(is_zero_config);
+        let bool_ops_chip = BoolOpsChip::<Fp>::construct(bool_ops_config);
+        let ship_cells_chip = ShipCellsChip::<Fp>::construct(ship_cells_config);
+        let hit_list_chip = HitListChip::<Fp>::construct(hit_list_config);
+
+        let hits = hit_list_chip.load_hits(
+            layouter.namespace(|| "hit_list"),
+            instance_col,
+            STATUS_COMMITMENTS_INSTANCE_OFFSET + self.ships.len(),
+            self.max_hits,
+        )?;
+
+        // one hit bit per committed ship cell
+        let mut cell_hit_bits = Vec::new();
+        for (i, ship) in self.ships.iter().enumerate() {
+            let hashed = hash_ship(&mut layouter, &poseidon_config, advice_col, ship, i)?;
+            layouter.constrain_instance(
+                hashed.commitment.cell(),
+                instance_col,
+                STATUS_COMMITMENTS_INSTANCE_OFFSET + i,
+            )?;
+            constrain_ship_length(&mut layouter, &hashed.length, self.fleet[i])?;
+
+            let cells = ship_cells_chip.assign(
+                layouter.namespace(|| format!("ship_cells_{}", i)),
+                &hashed.x,
+                &hashed.y,
+                &hashed.orientation,
+                self.fleet[i],
+            )?;
+
+            for (k, cell) in cells.iter().enumerate() {
+                let product = hit_list_chip.product(
+                    layouter.namespace(|| format!("cell_{}_{}_hit", i, k)),
+                    cell,
+                    &hits,
+                )?;
+
+                let is_hit = layouter.assign_region(
+                    || format!("cell_{}_{}_is_hit", i, k),
+                    |mut region| {
+                        q_cell_hit.enable(&mut region, 0)?;
+                        product.copy_advice(|| "product", &mut region, cols[0], 0)?;
+                        is_zero_chip.assign(&mut region, 0, product.value().copied())?;
+
+                        let is_hit = product.value().map(|p| {
+                            if p.is_zero_vartime() { Fp::ONE } else { Fp::ZERO }
+                        });
+                        region.assign_advice(|| "is_hit", cols[2], 0, || is_hit)
+                    },
+                )?;
+                cell_hit_bits.push(is_hit);
+            }
+        }
+
+        // all_sunk = hit_0 AND hit_1 AND ... AND hit_n (an empty fleet counts as sunk)
+        let computed_all_sunk = match cell_hit_bits.split_first() {
+            None => layouter.assign_region(
+                || "compute_all_sunk",
+                |mut region| region.assign_advice_from_constant(|| "all_sunk_empty", cols[2], 0, Fp::ONE),
+            )?,
+            Some((first, rest)) => {
+                let mut acc = first.clone();
+                for (j, cell) in rest.iter().enumerate() {
+                    acc = bool_ops_chip.and(layouter.namespace(|| format!("compute_all_sunk_{}", j)), &acc, cell)?;
+                }
+                acc
+            }
+        };
+
+        // verify the claimed status against the computed one and expose it to the instance column
+        let status_cell = layouter.assign_region(
+            || "verify_and_expose_status",
+            |mut region| {
+                let computed_cell = computed_all_sunk.copy_advice(|| "computed_all_sunk", &mut region, cols[2], 0)?;
+                let claimed_cell = region.assign_advice(|| "claimed_all_sunk", cols[2], 1, || self.all_sunk)?;
+                region.constrain_equal(computed_cell.cell(), claimed_cell.cell())?;
+                Ok(computed_cell)
+            },
+        )?;
+
+        layouter.constrain_instance(status_cell.cell(), instance_col, STATUS_ALL_SUNK_INSTANCE_ROW)?;
+
+        Ok(())
+    }
+}
+
+// SNARK circuit for the second half of the range proof bridge: the Pallas Pedersen commitments of a
+// ship's x and y (from the bit by bit bridge) open to the same x and y as its Poseidon commitment
+#[derive(Clone)]
+struct ShipLinkCircuit {
+    ship: ShipWitness,        // private ship data (its commitment is a public input)
+    blindings: [Value<Fq>; 2], // Pallas blindings of the bridged x and y
+    value_bits: usize,        // bits the coordinates were range proved (and bridged) with
+}
+
+impl ShipLinkCircuit {
+    fn empty(value_bits: usize) -> Self {
+        ShipLinkCircuit {
+            ship: ShipWitness::unknown(),
+            blindings: [Value::unknown(); 2],
+            value_bits,
+        }
+    }
+}
+
+impl Circuit<Fp> for ShipLinkCircuit {
+
+    type Config = (
+        Pow5Config<Fp, 3, 2>,
+        Column<Advice>,        // advice_col
+        Column<Instance>,
+        PallasMulConfig,       // in-circuit Pallas Pedersen commitment
+    );
+
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            ship: ShipWitness::unknown(),
+            blindings: [Value::unknown(); 2],
+            value_bits: self.value_bits,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let poseidon_config = configure_poseidon(meta);
+
+        let advice = meta.advice_column();
+        let cols = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let instance = meta.instance_column();
+
+        meta.enable_equality(advice);
+        for col in cols {
+            meta.enable_equality(col);
+        }
+        meta.enable_equality(instance);
+
+        let pallas_mul_config = PallasMulChip::configure(meta, cols[0], cols[1], cols[2], cols[3], cols[4], cols[5]);
+
+        (poseidon_config, advice, instance, pallas_mul_config)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+
+        let (poseidon_config, advice_col, instance_col, pallas_mul_config) = config;
+
+        let (g, h, offset) = pallas_bridge_generators();
+        let pallas_mul_chip = PallasMulChip::construct(pallas_mul_config, g, h, offset);
+
+        let hashed = hash_ship(&mut layouter, &poseidon_config, advice_col, &self.ship, 0)?;
+        layouter.constrain_instance(hashed.commitment.cell(), instance_col, LINK_COMMITMENT_INSTANCE_ROW)?;
+
+        for (axis, (coord, blinding)) in [(&hashed.x, self.blindings[0]), (&hashed.y, self.blindings[1])]
+            .into_iter()
+            .enumerate()
+        {
+            let (acc_x, acc_y) = pallas_mul_chip.assign(
+                layouter.namespace(|| format!("pallas_commitment_{}", axis)),
+                coord,
+                self.value_bits,
+                blinding,
+            )?;
+            let row = LINK_POINTS_INSTANCE_OFFSET + 2 * axis;
+            layouter.constrain_instance(acc_x.cell(), instance_col, row)?;
+            layouter.constrain_instance(acc_y.cell(), instance_col, row + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+// generates the proof
+fn generate_proof<C: Circuit<Fp>>(
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    circuit: C,
+    public_inputs: &[Fp],
+) -> Vec<u8> {
+    let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<EqAffine>>::init(vec![]);
+    
+    create_proof(
+        params,
+        pk,
         &[circuit],
         &[&[public_inputs]],
         OsRng,
@@ -728,167 +3074,2753 @@ fn generate_proof(
     )
     .expect("Proof generation should not fail");
 
-    transcript.finalize()
+    transcript.finalize()
+}
+
+// verifies the generated proof
+fn verify_proof_strat(
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    proof: &[u8],
+    public_inputs: &[Fp],
+) -> bool {
+    
+    let strategy = SingleVerifier::new(params);
+    let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(proof);
+
+    verify_proof(
+        params,
+        vk,
+        strategy,
+        &[&[public_inputs]],
+        &mut transcript,
+    )
+    .is_ok()
+}
+
+// the circuit has 2^K rows and K used to be hardcoded to 8, which stops fitting once the board, the fleet or the circuits grow.
+// instead the circuits are laid out once without witnesses and the rows they use are counted.
+// RowCounter is the dry run, it only remembers the last row anything was assigned in
+struct RowCounter {
+    rows: usize,
+    constants_column: Column<Fixed>,
+    constants: usize,
+}
+
+impl Assignment<Fp> for RowCounter {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.rows = self.rows.max(row + 1);
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<Fp>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(&mut self, _: A, _: Column<Advice>, row: usize, _: V) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<Fp>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.rows = self.rows.max(row + 1);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(&mut self, _: A, column: Column<Fixed>, row: usize, _: V) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<Fp>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        // constants (like the poseidon round constants) get put in rows after the circuit by the floor planner
+        if column == self.constants_column {
+            self.constants += 1;
+        } else {
+            self.rows = self.rows.max(row + 1);
+        }
+        Ok(())
+    }
+
+    fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn fill_from_row(&mut self, _: Column<Fixed>, _: usize, _: Value<Assigned<Fp>>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+// smallest K the circuit fits in: its rows, the constants, and the rows halo2 keeps back for blinding
+fn circuit_k<C: Circuit<Fp>>(circuit: &C) -> u32 {
+    let mut cs = ConstraintSystem::default();
+    let config = C::configure(&mut cs);
+    let constants_column = cs.fixed_column();
+    let mut counter = RowCounter { rows: 0, constants_column, constants: 0 };
+    C::FloorPlanner::synthesize(&mut counter, circuit, config, vec![constants_column])
+        .expect("dry run of the circuit layout failed");
+    let rows = (counter.rows + counter.constants + cs.blinding_factors() + 1).max(cs.minimum_rows());
+    rows.next_power_of_two().trailing_zeros()
+}
+
+// K the params need for a game with these rules, the biggest of the circuits the game uses
+fn required_k(rules: &GameRules) -> u32 {
+    let (grid_size, fleet) = (rules.grid_size, &rules.fleet);
+    let mut k = 0;
+    match rules.commitment_mode {
+        CommitmentMode::PerShip => {
+            k = k.max(circuit_k(&FleetSetupCircuit::empty(grid_size, fleet, rules.adjacency)));
+            k = k.max(circuit_k(&BattleshipCircuit::empty(grid_size, fleet)));
+            k = k.max(circuit_k(&ShipLinkCircuit::empty(range_bits(grid_size))));
+            k = k.max(circuit_k(&FleetStatusCircuit::empty(fleet)));
+            for &length in fleet {
+                k = k.max(circuit_k(&SunkShipCircuit::empty(length, fleet)));
+            }
+        }
+        CommitmentMode::Board => {
+            k = k.max(circuit_k(&MerkleShotCircuit::empty(grid_size)));
+            k = k.max(circuit_k(&BoardSetupCircuit::empty(grid_size, fleet)));
+        }
+    }
+    k
+}
+
+// instance column layout for the shot circuit: [hit, attack_x, attack_y, commitment_0, ..., commitment_n]
+const HIT_INSTANCE_ROW: usize = 0;
+const ATTACK_X_INSTANCE_ROW: usize = 1;
+const ATTACK_Y_INSTANCE_ROW: usize = 2;
+const COMMITMENTS_INSTANCE_OFFSET: usize = 3;
+
+// board commitment mode uses [hit, attack_x, attack_y, root] instead
+const BOARD_ROOT_INSTANCE_ROW: usize = 3;
+
+// and its setup proof just [root]
+const BOARD_SETUP_ROOT_INSTANCE_ROW: usize = 0;
+
+// sunk ship proofs use [commitment, hit_x_0, hit_y_0, ..., hit_x_m, hit_y_m]
+const SUNK_COMMITMENT_INSTANCE_ROW: usize = 0;
+const SUNK_HITS_INSTANCE_OFFSET: usize = 1;
+
+// fleet status proofs use [all_sunk, commitment_0, ..., commitment_n, hit_x_0, hit_y_0, ...]
+const STATUS_ALL_SUNK_INSTANCE_ROW: usize = 0;
+const STATUS_COMMITMENTS_INSTANCE_OFFSET: usize = 1;
+
+// ship link proofs use [commitment, x_point_x, x_point_y, y_point_x, y_point_y]
+// where each point is the bridged Pallas commitment plus the fixed offset point
+const LINK_COMMITMENT_INSTANCE_ROW: usize = 0;
+const LINK_POINTS_INSTANCE_OFFSET: usize = 1;
+
+// public inputs for the fleet setup proof are just the published commitments
+fn setup_public_inputs(commitments: &[Fp]) -> Vec<Fp> {
+    commitments.to_vec()
+}
+
+// builds the public inputs for a shot proof in the same order the circuit exposes them
+fn shot_public_inputs(hit: bool, attack_x: u8, attack_y: u8, commitments: &[Fp]) -> Vec<Fp> {
+    let mut public_inputs = vec![
+        Fp::from(if hit { 1u64 } else { 0u64 }),
+        Fp::from(attack_x as u64),
+        Fp::from(attack_y as u64),
+    ];
+    public_inputs.extend_from_slice(commitments);
+    public_inputs
+}
+
+// the public hit list as (x, y) pairs, padded to max_hits with (width, height)
+// which is off the board so it can never match a ship cell
+fn hit_list_public_inputs(hits: &[(u8, u8)], max_hits: usize, grid_size: GridSize) -> Vec<Fp> {
+    let padding = (grid_size.width as u64, grid_size.height as u64);
+    let mut public_inputs = Vec::new();
+    for j in 0..max_hits {
+        let (x, y) = hits.get(j).map(|&(x, y)| (x as u64, y as u64)).unwrap_or(padding);
+        public_inputs.push(Fp::from(x));
+        public_inputs.push(Fp::from(y));
+    }
+    public_inputs
+}
+
+// public inputs for a sunk ship proof
+fn sunk_public_inputs(commitment: Fp, hits: &[(u8, u8)], max_hits: usize, grid_size: GridSize) -> Vec<Fp> {
+    let mut public_inputs = vec![commitment];
+    public_inputs.extend(hit_list_public_inputs(hits, max_hits, grid_size));
+    public_inputs
+}
+
+// public inputs for a fleet status (game over / not sunk yet) proof
+fn status_public_inputs(
+    all_sunk: bool,
+    commitments: &[Fp],
+    hits: &[(u8, u8)],
+    max_hits: usize,
+    grid_size: GridSize,
+) -> Vec<Fp> {
+    let mut public_inputs = vec![Fp::from(if all_sunk { 1u64 } else { 0u64 })];
+    public_inputs.extend_from_slice(commitments);
+    public_inputs.extend(hit_list_public_inputs(hits, max_hits, grid_size));
+    public_inputs
+}
+
+// public inputs for a ship link proof, the verifier adds the offset to the bridged points itself
+fn link_public_inputs(commitment: Fp, x_bridge: &CoordinateBridge, y_bridge: &CoordinateBridge) -> Vec<Fp> {
+    let (_, _, offset) = pallas_bridge_generators();
+    let mut public_inputs = vec![commitment];
+    for bridge in [x_bridge, y_bridge] {
+        let (x, y) = point_coordinates(bridge.pallas_commitment + offset);
+        public_inputs.push(x);
+        public_inputs.push(y);
+    }
+    public_inputs
+}
+
+// public inputs for a shot proof in board commitment mode
+fn board_shot_public_inputs(hit: bool, attack_x: u8, attack_y: u8, root: Fp) -> Vec<Fp> {
+    vec![
+        Fp::from(if hit { 1u64 } else { 0u64 }),
+        Fp::from(attack_x as u64),
+        Fp::from(attack_y as u64),
+        root,
+    ]
+}
+
+// public inputs for the board setup proof are just the published root
+fn board_setup_public_inputs(root: Fp) -> Vec<Fp> {
+    vec![root]
+}
+
+// everything the owner of a board publishes at setup, this is all the opponent ever sees of it
+#[derive(Clone)]
+struct PublishedBoard {
+    grid_size: GridSize,
+    fleet: Vec<u8>,
+    adjacency: AdjacencyRule,
+    ship_commitments: Vec<Fp>,                  // Poseidon commitments only, the salts stay private
+    ship_range_proofs: Vec<ShipPlacementProof>, // bridged coordinate commitments
+    fleet_range_proof: Option<RangeProof>,      // aggregated range proof over all of them
+    board_root: Option<Fp>,                     // board commitment mode only
+}
+
+// a hit/miss answer with its SNARK and the public inputs it was proven against
+#[derive(Clone, Debug, PartialEq)]
+struct ShotProof {
+    attack_x: u8,
+    attack_y: u8,
+    hit: bool,
+    proof: Vec<u8>,
+    public_inputs: Vec<Fp>,
+}
+
+// opponent side view of one board: the published data plus the public history of the game
+// every check in run() goes through this, so it never sees ship positions, salts or blindings
+struct Verifier {
+    board: PublishedBoard,
+    hits: Vec<(u8, u8)>,          // confirmed hits on this board, in order
+    answers: Vec<(u8, u8, bool)>, // every verified (x, y, hit) answer, audited against the reveal
+    sunk: Vec<usize>,             // ships already proven sunk, by index
+}
+
+impl Verifier {
+    fn new(board: PublishedBoard) -> Self {
+        Verifier { board, hits: Vec::new(), answers: Vec::new(), sunk: Vec::new() }
+    }
+
+    // user facing debug output for commitments
+    fn print_commitments(&self, label: &str) {
+        if let Some(root) = &self.board.board_root {
+            println!("\n{} board commitment (Poseidon Merkle root):", label);
+            println!("- root => {:?}", root);
+            println!("(cell salts stay hidden to preserve secrecy)\n");
+            return;
+        }
+        println!("\n{} commitments (Poseidon digests):", label);
+        if self.board.ship_commitments.is_empty() {
+            println!("- none committed yet");
+            return;
+        }
+        for (idx, commitment) in self.board.ship_commitments.iter().enumerate() {
+            println!("- #{} => {:?}", idx + 1, commitment);
+        }
+        println!("(hashes are public; salts stay hidden to preserve secrecy)\n");
+    }
+
+    // how many commitments were published for this board
+    fn commitments_count(&self) -> usize {
+        match self.board.board_root {
+            Some(_) => 1,
+            None => self.board.ship_commitments.len(),
+        }
+    }
+
+    // batch verifies the aggregated fleet range proof: every origin x is in [0, width) and every y in [0, height)
+    fn verify_ship_proofs(&self) -> bool {
+        let Some(proof) = &self.board.fleet_range_proof else {
+            return false;
+        };
+        let Some(commitments) = fleet_range_commitments(&self.board.ship_range_proofs, self.board.grid_size) else {
+            return false;
+        };
+
+        let bits = range_bits(self.board.grid_size);
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(bits, commitments.len());
+        let mut transcript = Transcript::new(b"battleship_fleet_range_proof");
+        proof.verify_multiple(&bp_gens, &pc_gens, &mut transcript, &commitments, bits).is_ok()
+    }
+
+    // fleet setup proof against the published commitments
+    fn verify_fleet_setup(&self, params: &Params<EqAffine>, vk: &VerifyingKey<EqAffine>, proof: &[u8]) -> bool {
+        self.board.ship_commitments.len() == self.board.fleet.len()
+            && verify_proof_strat(params, vk, proof, &setup_public_inputs(&self.board.ship_commitments))
+    }
+
+    // board mode setup: the published root commits to a board with the fleet's number of ship cells
+    fn verify_board_setup(&self, params: &Params<EqAffine>, vk: &VerifyingKey<EqAffine>, proof: &[u8]) -> bool {
+        self.board.board_root.is_some_and(|root| verify_proof_strat(params, vk, proof, &board_setup_public_inputs(root)))
+    }
+
+    // checks the range proved coordinates are the ones in the Poseidon commitments:
+    // every bridge has to tie its Ristretto commitment to a Pallas one and every link SNARK has to open
+    // those Pallas commitments against the ship commitment
+    fn verify_ship_links(&self, params: &Params<EqAffine>, vk: &VerifyingKey<EqAffine>, proofs: &[Vec<u8>]) -> bool {
+        let bits = range_bits(self.board.grid_size);
+        let commitments = &self.board.ship_commitments;
+        let placements = &self.board.ship_range_proofs;
+
+        proofs.len() == commitments.len()
+            && placements.len() == commitments.len()
+            && proofs.iter().zip(commitments.iter()).zip(placements.iter()).all(|((proof, commitment), placement)| {
+                placement.x.bridge.verify(&placement.x.commitment, bits)
+                    && placement.y.bridge.verify(&placement.y.commitment, bits)
+                    && verify_proof_strat(
+                        params,
+                        vk,
+                        proof,
+                        &link_public_inputs(*commitment, &placement.x.bridge, &placement.y.bridge),
+                    )
+            })
+    }
+
+    // attacks are public so this is just the board bound, the shot circuit range checks them again
+    fn check_attack(&self, x: u8, y: u8) -> Result<(), String> {
+        if !self.board.grid_size.contains(x as usize, y as usize) {
+            return Err(format!("Coordinate ({}, {}) is off the {} board", x, y, self.board.grid_size));
+        }
+        Ok(())
+    }
+
+    // public inputs an answer about (x, y) has to be proven against on this board
+    fn shot_public_inputs(&self, attack_x: u8, attack_y: u8, hit: bool) -> Vec<Fp> {
+        match self.board.board_root {
+            Some(root) => board_shot_public_inputs(hit, attack_x, attack_y, root),
+            None => shot_public_inputs(hit, attack_x, attack_y, &self.board.ship_commitments),
+        }
+    }
+
+    // shot proof against what was published at setup. the public inputs are rebuilt here and
+    // the ones that came with the proof have to match them, so a proof can't bring its own commitments
+    fn verify_shot(&self, params: &Params<EqAffine>, vk: &VerifyingKey<EqAffine>, shot: &ShotProof) -> bool {
+        let public_inputs = self.shot_public_inputs(shot.attack_x, shot.attack_y, shot.hit);
+        shot.public_inputs == public_inputs && verify_proof_strat(params, vk, &shot.proof, &public_inputs)
+    }
+
+    // keeps a verified answer, hits also go into the public hit history
+    fn record_answer(&mut self, x: u8, y: u8, hit: bool) {
+        self.answers.push((x, y, hit));
+        if hit && !self.hits.contains(&(x, y)) {
+            self.hits.push((x, y));
+        }
+    }
+
+    // sunk claim for ship `index`, using only its commitment and the given hits.
+    // the hits are passed in so an answer can be checked before it gets recorded
+    fn verify_sunk(
+        &self,
+        params: &Params<EqAffine>,
+        vk: &VerifyingKey<EqAffine>,
+        proof: &[u8],
+        index: usize,
+        hits: &[(u8, u8)],
+    ) -> bool {
+        let max_hits = fleet_cells(&self.board.fleet);
+        let Some(&commitment) = self.board.ship_commitments.get(index) else {
+            return false;
+        };
+        hits.len() <= max_hits
+            && verify_proof_strat(params, vk, proof, &sunk_public_inputs(commitment, hits, max_hits, self.board.grid_size))
+    }
+
+    // game over (all_sunk) or not sunk yet (!all_sunk) claim against the commitments and the given hits
+    fn verify_fleet_status(
+        &self,
+        params: &Params<EqAffine>,
+        vk: &VerifyingKey<EqAffine>,
+        proof: &[u8],
+        all_sunk: bool,
+        hits: &[(u8, u8)],
+    ) -> bool {
+        let max_hits = fleet_cells(&self.board.fleet);
+        let public_inputs =
+            status_public_inputs(all_sunk, &self.board.ship_commitments, hits, max_hits, self.board.grid_size);
+        hits.len() <= max_hits && verify_proof_strat(params, vk, proof, &public_inputs)
+    }
+
+    // end of game audit of a reveal against everything published and answered
+    fn audit(&self, reveal: &BoardReveal) -> Vec<String> {
+        let published = match self.board.board_root {
+            Some(root) => vec![root],
+            None => self.board.ship_commitments.clone(),
+        };
+        audit_reveal(
+            reveal,
+            &published,
+            &self.answers,
+            &self.board.fleet,
+            self.board.grid_size,
+            self.board.adjacency,
+        )
+    }
+}
+
+// wire format for everything that gets sent to the opponent. every message starts with
+// [WIRE_VERSION][kind], fixed size values (field elements, points, scalars) are 32 bytes,
+// numbers are little endian and anything variable length has a u32 length prefix in front of it.
+const WIRE_VERSION: u8 = 1;
+
+const WIRE_SHIP_COMMITMENT: u8 = 1;
+const WIRE_COORDINATE_COMMITMENT: u8 = 2;
+const WIRE_SHIP_PLACEMENT_PROOF: u8 = 3;
+const WIRE_PUBLISHED_BOARD: u8 = 4;
+const WIRE_SHOT_PROOF: u8 = 5;
+const WIRE_ATTACK: u8 = 6;
+const WIRE_BOARD_SETUP: u8 = 7;
+const WIRE_SHOT_ANSWER: u8 = 8;
+const WIRE_BOARD_REVEAL: u8 = 9;
+const WIRE_SEAT: u8 = 10;
+
+// why a message couldn't be decoded
+#[derive(Debug, PartialEq, Eq)]
+enum WireError {
+    Truncated { needed: usize, remaining: usize },
+    UnsupportedVersion(u8),
+    WrongKind { expected: u8, found: u8 },
+    Malformed(String),
+    TrailingBytes(usize),
+}
+
+impl std::fmt::Display for WireError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WireError::Truncated { needed, remaining } => {
+                write!(f, "message truncated: needed {} more bytes but only {} left", needed, remaining)
+            }
+            WireError::UnsupportedVersion(version) => {
+                write!(f, "unsupported wire version {} (expected {})", version, WIRE_VERSION)
+            }
+            WireError::WrongKind { expected, found } => {
+                write!(f, "wrong message kind {} (expected {})", found, expected)
+            }
+            WireError::Malformed(what) => write!(f, "malformed message: {}", what),
+            WireError::TrailingBytes(count) => write!(f, "{} unexpected bytes after the message", count),
+        }
+    }
+}
+
+// builds up one message
+struct WireWriter {
+    bytes: Vec<u8>,
+}
+
+impl WireWriter {
+    fn new(kind: u8) -> Self {
+        WireWriter { bytes: vec![WIRE_VERSION, kind] }
+    }
+
+    fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    fn u32(&mut self, value: usize) {
+        // nothing in a game gets anywhere near 4GB, the reader bounds it again anyway
+        self.bytes.extend_from_slice(&(value as u32).to_le_bytes());
+    }
+
+    fn u128(&mut self, value: u128) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn fixed(&mut self, value: &[u8]) {
+        self.bytes.extend_from_slice(value);
+    }
+
+    fn fp(&mut self, value: &Fp) {
+        self.fixed(&value.to_repr());
+    }
+
+    fn fq(&mut self, value: &Fq) {
+        self.fixed(&value.to_repr());
+    }
+
+    fn pallas(&mut self, point: &pallas::Point) {
+        self.fixed(&point.to_bytes());
+    }
+
+    // length prefixed blob, nested messages go in with this
+    fn blob(&mut self, value: &[u8]) {
+        self.u32(value.len());
+        self.fixed(value);
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+// reads one message back, every read checks there are enough bytes left
+struct WireReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> WireReader<'a> {
+    // checks the header: the version has to be ours and the kind the one the caller expects
+    fn new(bytes: &'a [u8], kind: u8) -> Result<Self, WireError> {
+        let mut reader = WireReader { bytes, pos: 0 };
+        let version = reader.u8()?;
+        if version != WIRE_VERSION {
+            return Err(WireError::UnsupportedVersion(version));
+        }
+        let found = reader.u8()?;
+        if found != kind {
+            return Err(WireError::WrongKind { expected: kind, found });
+        }
+        Ok(reader)
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'a [u8], WireError> {
+        let remaining = self.bytes.len() - self.pos;
+        if count > remaining {
+            return Err(WireError::Truncated { needed: count, remaining });
+        }
+        let slice = &self.bytes[self.pos..self.pos + count];
+        self.pos += count;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], WireError> {
+        let mut out = [0u8; N];
+        out.copy_from_slice(self.take(N)?);
+        Ok(out)
+    }
+
+    fn u8(&mut self) -> Result<u8, WireError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<usize, WireError> {
+        Ok(u32::from_le_bytes(self.array()?) as usize)
+    }
+
+    fn u128(&mut self) -> Result<u128, WireError> {
+        Ok(u128::from_le_bytes(self.array()?))
+    }
+
+    fn bool(&mut self) -> Result<bool, WireError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            other => Err(WireError::Malformed(format!("{} is not a boolean", other))),
+        }
+    }
+
+    // count prefix for a list, checked against what's left so a bogus length can't make us allocate a lot
+    fn count(&mut self, min_item_size: usize) -> Result<usize, WireError> {
+        let count = self.u32()?;
+        let remaining = self.bytes.len() - self.pos;
+        if count.saturating_mul(min_item_size) > remaining {
+            return Err(WireError::Truncated { needed: count * min_item_size, remaining });
+        }
+        Ok(count)
+    }
+
+    fn fp(&mut self) -> Result<Fp, WireError> {
+        Option::from(Fp::from_repr(self.array()?))
+            .ok_or_else(|| WireError::Malformed("non canonical Fp element".to_string()))
+    }
+
+    fn fq(&mut self) -> Result<Fq, WireError> {
+        Option::from(Fq::from_repr(self.array()?))
+            .ok_or_else(|| WireError::Malformed("non canonical Fq element".to_string()))
+    }
+
+    fn pallas(&mut self) -> Result<pallas::Point, WireError> {
+        Option::from(pallas::Point::from_bytes(&self.array()?))
+            .ok_or_else(|| WireError::Malformed("invalid Pallas point".to_string()))
+    }
+
+    fn ristretto(&mut self) -> Result<CompressedRistretto, WireError> {
+        let point = CompressedRistretto(self.array()?);
+        if point.decompress().is_none() {
+            return Err(WireError::Malformed("invalid Ristretto point".to_string()));
+        }
+        Ok(point)
+    }
+
+    fn scalar(&mut self) -> Result<Scalar, WireError> {
+        Option::from(Scalar::from_canonical_bytes(self.array()?))
+            .ok_or_else(|| WireError::Malformed("non canonical Ristretto scalar".to_string()))
+    }
+
+    fn blob(&mut self) -> Result<&'a [u8], WireError> {
+        let len = self.u32()?;
+        self.take(len)
+    }
+
+    // the whole input has to be used up
+    fn finish(self) -> Result<(), WireError> {
+        match self.bytes.len() - self.pos {
+            0 => Ok(()),
+            extra => Err(WireError::TrailingBytes(extra)),
+        }
+    }
+}
+
+impl PublishedCommitment {
+    // a ShipCommitment on the wire, commitment only
+    fn to_bytes(self) -> Vec<u8> {
+        let mut writer = WireWriter::new(WIRE_SHIP_COMMITMENT);
+        writer.fp(&self.0);
+        writer.finish()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, WireError> {
+        let mut reader = WireReader::new(bytes, WIRE_SHIP_COMMITMENT)?;
+        let commitment = PublishedCommitment(reader.fp()?);
+        reader.finish()?;
+        Ok(commitment)
+    }
+}
+
+// encoded size of one bit proof: 8 points/scalars of 32 bytes and the 16 byte challenge
+const BIT_PROOF_WIRE_SIZE: usize = 8 * 32 + 16;
+
+impl BitEqualityProof {
+    // bit proofs only ever travel inside a CoordinateCommitment so they have no header of their own
+    fn write(&self, writer: &mut WireWriter) {
+        writer.fixed(self.ristretto_commitment.as_bytes());
+        writer.pallas(&self.pallas_commitment);
+        for t in &self.t_ristretto {
+            writer.fixed(t.as_bytes());
+        }
+        for t in &self.t_pallas {
+            writer.pallas(t);
+        }
+        writer.u128(self.e0);
+        for z in &self.z_ristretto {
+            writer.fixed(z.as_bytes());
+        }
+        for z in &self.z_pallas {
+            writer.fq(z);
+        }
+    }
+
+    fn read(reader: &mut WireReader) -> Result<Self, WireError> {
+        Ok(BitEqualityProof {
+            ristretto_commitment: reader.ristretto()?,
+            pallas_commitment: reader.pallas()?,
+            t_ristretto: [reader.ristretto()?, reader.ristretto()?],
+            t_pallas: [reader.pallas()?, reader.pallas()?],
+            e0: reader.u128()?,
+            z_ristretto: [reader.scalar()?, reader.scalar()?],
+            z_pallas: [reader.fq()?, reader.fq()?],
+        })
+    }
+}
+
+// the coordinate proof: the commitment and its bridge, the range proof itself is the fleet's aggregated one
+impl CoordinateCommitment {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = WireWriter::new(WIRE_COORDINATE_COMMITMENT);
+        writer.fixed(self.commitment.as_bytes());
+        writer.pallas(&self.bridge.pallas_commitment);
+        writer.u32(self.bridge.bits.len());
+        for bit in &self.bridge.bits {
+            bit.write(&mut writer);
+        }
+        writer.finish()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, WireError> {
+        let mut reader = WireReader::new(bytes, WIRE_COORDINATE_COMMITMENT)?;
+        let commitment = reader.ristretto()?;
+        let pallas_commitment = reader.pallas()?;
+        let count = reader.count(BIT_PROOF_WIRE_SIZE)?;
+        let bits = (0..count)
+            .map(|_| BitEqualityProof::read(&mut reader))
+            .collect::<Result<Vec<_>, _>>()?;
+        reader.finish()?;
+        Ok(CoordinateCommitment { commitment, bridge: CoordinateBridge { pallas_commitment, bits } })
+    }
+}
+
+impl ShipPlacementProof {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = WireWriter::new(WIRE_SHIP_PLACEMENT_PROOF);
+        writer.blob(&self.x.to_bytes());
+        writer.blob(&self.y.to_bytes());
+        writer.finish()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, WireError> {
+        let mut reader = WireReader::new(bytes, WIRE_SHIP_PLACEMENT_PROOF)?;
+        let x = CoordinateCommitment::from_bytes(reader.blob()?)?;
+        let y = CoordinateCommitment::from_bytes(reader.blob()?)?;
+        reader.finish()?;
+        Ok(ShipPlacementProof { x, y })
+    }
+}
+
+impl PublishedBoard {
+    // the whole setup message one side sends the other
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = WireWriter::new(WIRE_PUBLISHED_BOARD);
+        writer.u32(self.grid_size.width);
+        writer.u32(self.grid_size.height);
+        writer.blob(&self.fleet);
+        writer.u8(match self.adjacency {
+            AdjacencyRule::Allowed => 0,
+            AdjacencyRule::Forbidden => 1,
+        });
+        writer.u32(self.ship_commitments.len());
+        for &commitment in &self.ship_commitments {
+            writer.blob(&PublishedCommitment(commitment).to_bytes());
+        }
+        writer.u32(self.ship_range_proofs.len());
+        for placement in &self.ship_range_proofs {
+            writer.blob(&placement.to_bytes());
+        }
+        match &self.fleet_range_proof {
+            Some(proof) => {
+                writer.u8(1);
+                writer.blob(&proof.to_bytes());
+            }
+            None => writer.u8(0),
+        }
+        match &self.board_root {
+            Some(root) => {
+                writer.u8(1);
+                writer.fp(root);
+            }
+            None => writer.u8(0),
+        }
+        writer.finish()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, WireError> {
+        let mut reader = WireReader::new(bytes, WIRE_PUBLISHED_BOARD)?;
+        let grid_size = GridSize { width: reader.u32()?, height: reader.u32()? };
+        if [grid_size.width, grid_size.height].iter().any(|&size| size == 0 || size > u8::MAX as usize + 1) {
+            return Err(WireError::Malformed(format!("{} board doesn't fit u8 coordinates", grid_size)));
+        }
+        let fleet = reader.blob()?.to_vec();
+        let adjacency = match reader.u8()? {
+            0 => AdjacencyRule::Allowed,
+            1 => AdjacencyRule::Forbidden,
+            other => return Err(WireError::Malformed(format!("unknown adjacency rule {}", other))),
+        };
+
+        // every list entry is a length prefixed nested message, so at least its 4 byte prefix
+        let count = reader.count(4)?;
+        let ship_commitments = (0..count)
+            .map(|_| PublishedCommitment::from_bytes(reader.blob()?).map(|commitment| commitment.0))
+            .collect::<Result<Vec<_>, _>>()?;
+        let count = reader.count(4)?;
+        let ship_range_proofs = (0..count)
+            .map(|_| ShipPlacementProof::from_bytes(reader.blob()?))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let fleet_range_proof = match reader.bool()? {
+            true => Some(
+                RangeProof::from_bytes(reader.blob()?)
+                    .map_err(|e| WireError::Malformed(format!("fleet range proof: {:?}", e)))?,
+            ),
+            false => None,
+        };
+        let board_root = match reader.bool()? {
+            true => Some(reader.fp()?),
+            false => None,
+        };
+        reader.finish()?;
+
+        Ok(PublishedBoard {
+            grid_size,
+            fleet,
+            adjacency,
+            ship_commitments,
+            ship_range_proofs,
+            fleet_range_proof,
+            board_root,
+        })
+    }
+}
+
+impl ShotProof {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = WireWriter::new(WIRE_SHOT_PROOF);
+        writer.u8(self.attack_x);
+        writer.u8(self.attack_y);
+        writer.u8(self.hit as u8);
+        writer.u32(self.public_inputs.len());
+        for input in &self.public_inputs {
+            writer.fp(input);
+        }
+        writer.blob(&self.proof);
+        writer.finish()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, WireError> {
+        let mut reader = WireReader::new(bytes, WIRE_SHOT_PROOF)?;
+        let attack_x = reader.u8()?;
+        let attack_y = reader.u8()?;
+        let hit = reader.bool()?;
+        let count = reader.count(32)?;
+        let public_inputs = (0..count).map(|_| reader.fp()).collect::<Result<Vec<_>, _>>()?;
+        let proof = reader.blob()?.to_vec();
+        reader.finish()?;
+
+        // the shot part of the public inputs has to say the same thing as the header fields
+        let expected = [Fp::from(hit as u64), Fp::from(attack_x as u64), Fp::from(attack_y as u64)];
+        if public_inputs.len() < expected.len()
+            || public_inputs[HIT_INSTANCE_ROW] != expected[0]
+            || public_inputs[ATTACK_X_INSTANCE_ROW] != expected[1]
+            || public_inputs[ATTACK_Y_INSTANCE_ROW] != expected[2]
+        {
+            return Err(WireError::Malformed("public inputs don't match the shot".to_string()));
+        }
+
+        Ok(ShotProof { attack_x, attack_y, hit, proof, public_inputs })
+    }
+}
+
+// params file layout: magic, format version, K, blake2b hash of the params, then the params the way halo2 writes them.
+// the header is checked on load so a file for another K or a damaged one gets a clear error instead of failing keygen
+const PARAMS_MAGIC: [u8; 8] = *b"ZKBSPRMS";
+const PARAMS_VERSION: u8 = 1;
+const PARAMS_HASH_SIZE: usize = 32;
+const PARAMS_HEADER_SIZE: usize = PARAMS_MAGIC.len() + 1 + 4 + PARAMS_HASH_SIZE;
+
+// every K has its own params file, so params for one configuration never stand in the way of another one's
+fn params_path(k: u32) -> String {
+    format!("params_k{}.bin", k)
+}
+
+// why a params file couldn't be used
+#[derive(Debug, PartialEq, Eq)]
+enum ParamsError {
+    Missing,
+    Io(String),
+    NotAParamsFile, // no header, e.g. a raw params file from before the header was added
+    UnsupportedVersion(u8),
+    Corrupt(String),
+    WrongK { stored: u32, required: u32 },
+}
+
+impl std::fmt::Display for ParamsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParamsError::Missing => write!(f, "file not found"),
+            ParamsError::Io(e) => write!(f, "couldn't read it: {}", e),
+            ParamsError::NotAParamsFile => {
+                write!(f, "no params header (made by an older build?), generate new parameters")
+            }
+            ParamsError::UnsupportedVersion(version) => {
+                write!(f, "unsupported format version {}, generate new parameters", version)
+            }
+            ParamsError::Corrupt(what) => write!(f, "corrupt ({}), generate new parameters", what),
+            ParamsError::WrongK { stored, required } => write!(
+                f,
+                "params are for K = {} but the circuits need K = {}, generate new parameters",
+                stored, required
+            ),
+        }
+    }
+}
+
+fn params_hash(bytes: &[u8]) -> blake2b_simd::Hash {
+    blake2b_simd::Params::new().hash_length(PARAMS_HASH_SIZE).hash(bytes)
+}
+
+// K the params were made for, halo2 only exposes it through the number of generators
+fn params_k(params: &Params<EqAffine>) -> u32 {
+    params.get_g().len().trailing_zeros()
+}
+
+// generates params for 2^k rows and writes them to a file
+fn write_params(path: &str, k: u32) {
+    let params: Params<EqAffine> = Params::new(k);
+    let mut bytes = Vec::new();
+    params.write(&mut bytes).unwrap();
+
+    let mut params_file = File::create(path).unwrap();
+    params_file.write_all(&PARAMS_MAGIC).unwrap();
+    params_file.write_all(&[PARAMS_VERSION]).unwrap();
+    params_file.write_all(&k.to_le_bytes()).unwrap();
+    params_file.write_all(params_hash(&bytes).as_bytes()).unwrap();
+    params_file.write_all(&bytes).unwrap();
+}
+
+// reads a params file and checks its header. the K has to be exactly `required_k`, the keys depend on it
+// so both players (and anyone checking a transcript) have to use the same one. a file keyed by K only
+// fails this when it was renamed or copied over
+fn read_params(path: &str, required_k: u32) -> Result<Params<EqAffine>, ParamsError> {
+    let file = std::fs::read(path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => ParamsError::Missing,
+        _ => ParamsError::Io(e.to_string()),
+    })?;
+    if file.len() < PARAMS_HEADER_SIZE || file[..PARAMS_MAGIC.len()] != PARAMS_MAGIC {
+        return Err(ParamsError::NotAParamsFile);
+    }
+    let (header, bytes) = file.split_at(PARAMS_HEADER_SIZE);
+    let version = header[PARAMS_MAGIC.len()];
+    if version != PARAMS_VERSION {
+        return Err(ParamsError::UnsupportedVersion(version));
+    }
+    let k_offset = PARAMS_MAGIC.len() + 1;
+    let stored = u32::from_le_bytes(header[k_offset..k_offset + 4].try_into().expect("4 bytes"));
+    if stored != required_k {
+        return Err(ParamsError::WrongK { stored, required: required_k });
+    }
+    if header[k_offset + 4..] != *params_hash(bytes).as_bytes() {
+        return Err(ParamsError::Corrupt("hash doesn't match the contents".to_string()));
+    }
+
+    let mut reader = bytes;
+    let params = Params::<EqAffine>::read(&mut reader).map_err(|e| ParamsError::Corrupt(e.to_string()))?;
+    if !reader.is_empty() {
+        return Err(ParamsError::Corrupt(format!("{} bytes after the params", reader.len())));
+    }
+    if params_k(&params) != stored {
+        return Err(ParamsError::Corrupt(format!("header says K = {} but the params are for K = {}", stored, params_k(&params))));
+    }
+    Ok(params)
+}
+
+// reads the params for `required_k`, generating them first if their file is missing. a bad file is an error,
+// it only gets replaced when new parameters are generated on purpose
+fn initialize_params(required_k: u32) -> Result<Params<EqAffine>, String> {
+    let path = params_path(required_k);
+    let params = match read_params(&path, required_k) {
+        Err(ParamsError::Missing) => {
+            println!("\nParams file {} not found. Please generate parameters first.", path);
+            println!("Generating parameters for K = {} now...", required_k);
+            write_params(&path, required_k);
+            println!("Parameters generated successfully!\n");
+            read_params(&path, required_k)
+        }
+        result => result,
+    };
+    params.map_err(|e| format!("Can't use {}: {}", path, e))
+}
+
+// generates the proving and verifying keys for the per ship hit/miss circuit
+fn initialize_shot_keys(params: &Params<EqAffine>, grid_size: GridSize, fleet: &[u8]) -> (
+    ProvingKey<EqAffine>,
+    VerifyingKey<EqAffine>
+) {
+    let empty_circuit = BattleshipCircuit::empty(grid_size, fleet);
+
+    // generates proving and verifying keys
+    let vk = keygen_vk(params, &empty_circuit).expect("Failed to generate verifying key");
+    let pk = keygen_pk(params, vk.clone(), &empty_circuit).expect( "Failed to generate proving key");
+
+    (pk, vk)
+}
+
+// generates the proving and verifying keys for the fleet setup circuit
+fn initialize_setup_keys(
+    params: &Params<EqAffine>,
+    grid_size: GridSize,
+    fleet: &[u8],
+    adjacency: AdjacencyRule,
+) -> (
+    ProvingKey<EqAffine>,
+    VerifyingKey<EqAffine>
+) {
+    let empty_circuit = FleetSetupCircuit::empty(grid_size, fleet, adjacency);
+
+    let vk = keygen_vk(params, &empty_circuit).expect("Failed to generate setup verifying key");
+    let pk = keygen_pk(params, vk.clone(), &empty_circuit).expect("Failed to generate setup proving key");
+
+    (pk, vk)
+}
+
+// generates the proving and verifying keys for the board commitment (Merkle path) shot circuit
+fn initialize_board_keys(params: &Params<EqAffine>, grid_size: GridSize) -> (
+    ProvingKey<EqAffine>,
+    VerifyingKey<EqAffine>
+) {
+    let empty_circuit = MerkleShotCircuit::empty(grid_size);
+
+    let vk = keygen_vk(params, &empty_circuit).expect("Failed to generate board verifying key");
+    let pk = keygen_pk(params, vk.clone(), &empty_circuit).expect("Failed to generate board proving key");
+
+    (pk, vk)
+}
+
+// generates the proving and verifying keys for the board commitment mode setup circuit
+fn initialize_board_setup_keys(params: &Params<EqAffine>, grid_size: GridSize, fleet: &[u8]) -> (
+    ProvingKey<EqAffine>,
+    VerifyingKey<EqAffine>
+) {
+    let empty_circuit = BoardSetupCircuit::empty(grid_size, fleet);
+
+    let vk = keygen_vk(params, &empty_circuit).expect("Failed to generate board setup verifying key");
+    let pk = keygen_pk(params, vk.clone(), &empty_circuit).expect("Failed to generate board setup proving key");
+
+    (pk, vk)
+}
+
+// generates the proving and verifying keys for the ship link (range proof bridge) circuit
+fn initialize_link_keys(params: &Params<EqAffine>, value_bits: usize) -> (
+    ProvingKey<EqAffine>,
+    VerifyingKey<EqAffine>
+) {
+    let empty_circuit = ShipLinkCircuit::empty(value_bits);
+
+    let vk = keygen_vk(params, &empty_circuit).expect("Failed to generate link verifying key");
+    let pk = keygen_pk(params, vk.clone(), &empty_circuit).expect("Failed to generate link proving key");
+
+    (pk, vk)
+}
+
+// sunk ship keys per ship length, the length decides how many cell rows the circuit has
+type SunkKeys = BTreeMap<u8, (ProvingKey<EqAffine>, VerifyingKey<EqAffine>)>;
+
+// generates the sunk ship keys for every ship length in the fleet
+fn initialize_sunk_keys(params: &Params<EqAffine>, fleet: &[u8]) -> SunkKeys {
+    let mut keys = SunkKeys::new();
+    for &length in fleet {
+        if keys.contains_key(&length) {
+            continue;
+        }
+        let empty_circuit = SunkShipCircuit::empty(length, fleet);
+
+        let vk = keygen_vk(params, &empty_circuit).expect("Failed to generate sunk ship verifying key");
+        let pk = keygen_pk(params, vk.clone(), &empty_circuit).expect("Failed to generate sunk ship proving key");
+        keys.insert(length, (pk, vk));
+    }
+    keys
+}
+
+// generates the proving and verifying keys for the fleet status (game over / not sunk yet) circuit
+fn initialize_status_keys(params: &Params<EqAffine>, fleet: &[u8]) -> (
+    ProvingKey<EqAffine>,
+    VerifyingKey<EqAffine>
+) {
+    let empty_circuit = FleetStatusCircuit::empty(fleet);
+
+    let vk = keygen_vk(params, &empty_circuit).expect("Failed to generate status verifying key");
+    let pk = keygen_pk(params, vk.clone(), &empty_circuit).expect("Failed to generate status proving key");
+
+    (pk, vk)
+}
+
+// every key a game needs for the chosen rules
+struct GameKeys {
+    shot: (ProvingKey<EqAffine>, VerifyingKey<EqAffine>),
+    setup: (ProvingKey<EqAffine>, VerifyingKey<EqAffine>),
+    link: Option<(ProvingKey<EqAffine>, VerifyingKey<EqAffine>)>, // per ship mode only
+    sunk: Option<SunkKeys>,                                         // per ship mode only
+    status: Option<(ProvingKey<EqAffine>, VerifyingKey<EqAffine>)>, // per ship mode only
+}
+
+// the verifying half of the game keys, all a match needs to check the proofs both sides send
+struct VerifyingKeys {
+    shot: VerifyingKey<EqAffine>,
+    setup: VerifyingKey<EqAffine>,
+    link: Option<VerifyingKey<EqAffine>>,               // per ship mode only
+    sunk: Option<BTreeMap<u8, VerifyingKey<EqAffine>>>, // per ship mode only
+    status: Option<VerifyingKey<EqAffine>>,             // per ship mode only
+}
+
+impl GameKeys {
+    // verifying keys of a side that also proves, copied out so a match can hold them
+    fn verifying(&self) -> VerifyingKeys {
+        VerifyingKeys {
+            shot: self.shot.1.clone(),
+            setup: self.setup.1.clone(),
+            link: self.link.as_ref().map(|(_, vk)| vk.clone()),
+            sunk: self
+                .sunk
+                .as_ref()
+                .map(|keys| keys.iter().map(|(&length, (_, vk))| (length, vk.clone())).collect()),
+            status: self.status.as_ref().map(|(_, vk)| vk.clone()),
+        }
+    }
+}
+
+// generates every key a game with these rules needs. halo2_proofs 0.3 has no way to write keys
+// to disk and read them back, so they can't be cached and get generated again on every launch
+fn initialize_game_keys(params: &Params<EqAffine>, rules: &GameRules) -> GameKeys {
+    let (grid_size, fleet) = (rules.grid_size, &rules.fleet);
+    match rules.commitment_mode {
+        // sunk ship, fleet status and link proofs open the per ship commitments, so they only exist in per ship mode
+        CommitmentMode::PerShip => GameKeys {
+            shot: initialize_shot_keys(params, grid_size, fleet),
+            setup: initialize_setup_keys(params, grid_size, fleet, rules.adjacency),
+            link: Some(initialize_link_keys(params, range_bits(grid_size))),
+            sunk: Some(initialize_sunk_keys(params, fleet)),
+            status: Some(initialize_status_keys(params, fleet)),
+        },
+        // shot proofs are made against whatever each side published at setup
+        CommitmentMode::Board => GameKeys {
+            shot: initialize_board_keys(params, grid_size),
+            setup: initialize_board_setup_keys(params, grid_size, fleet),
+            link: None,
+            sunk: None,
+            status: None,
+        },
+    }
+}
+
+// keygen_vk gives the same key keygen_pk is built on, so these check proofs made with the full keys
+fn verifying_key<C: Circuit<Fp>>(params: &Params<EqAffine>, circuit: &C) -> VerifyingKey<EqAffine> {
+    keygen_vk(params, circuit).expect("Failed to generate verifying key")
+}
+
+// only the verifying keys for these rules, for a side that checks proofs but never makes any
+// (the referee, transcript verification). it skips keygen_pk, which is most of the key generation time
+fn initialize_verifying_keys(params: &Params<EqAffine>, rules: &GameRules) -> VerifyingKeys {
+    let (grid_size, fleet) = (rules.grid_size, &rules.fleet);
+    match rules.commitment_mode {
+        CommitmentMode::PerShip => {
+            let lengths: std::collections::BTreeSet<u8> = fleet.iter().copied().collect();
+            VerifyingKeys {
+                shot: verifying_key(params, &BattleshipCircuit::empty(grid_size, fleet)),
+                setup: verifying_key(params, &FleetSetupCircuit::empty(grid_size, fleet, rules.adjacency)),
+                link: Some(verifying_key(params, &ShipLinkCircuit::empty(range_bits(grid_size)))),
+                sunk: Some(
+                    lengths
+                        .into_iter()
+                        .map(|length| (length, verifying_key(params, &SunkShipCircuit::empty(length, fleet))))
+                        .collect(),
+                ),
+                status: Some(verifying_key(params, &FleetStatusCircuit::empty(fleet))),
+            }
+        }
+        CommitmentMode::Board => VerifyingKeys {
+            shot: verifying_key(params, &MerkleShotCircuit::empty(grid_size)),
+            setup: verifying_key(params, &BoardSetupCircuit::empty(grid_size, fleet)),
+            link: None,
+            sunk: None,
+            status: None,
+        },
+    }
+}
+
+// total number of ship cells in a fleet, this is the most hits a board can ever take
+fn fleet_cells(fleet: &[u8]) -> usize {
+    fleet.iter().map(|&length| length as usize).sum()
+}
+
+// opponent side audit of a reveal: every opening has to match what was published at setup,
+// the revealed fleet has to follow the rules and every answer given during the game has to agree
+// with the revealed board. returns the problems found, empty means the side played honestly
+fn audit_reveal(
+    reveal: &BoardReveal,
+    published: &[Fp],
+    answers: &[(u8, u8, bool)],
+    fleet: &[u8],
+    grid_size: GridSize,
+    adjacency: AdjacencyRule,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if reveal.ships.len() != fleet.len() {
+        problems.push(format!("revealed {} ships but the fleet has {}", reveal.ships.len(), fleet.len()));
+    }
+
+    // the revealed ships have to be the fleet and fit on the board
+    let mut revealed_cells = Vec::new();
+    for (i, (ship, _)) in reveal.ships.iter().enumerate() {
+        if fleet.get(i) != Some(&ship.length) {
+            problems.push(format!("ship {} has length {} which isn't in the fleet spec", i, ship.length));
+        }
+        let (end_x, end_y) = match ship.orientation {
+            Orientation::Horizontal => (ship.x as usize + ship.length as usize, ship.y as usize + 1),
+            Orientation::Vertical => (ship.x as usize + 1, ship.y as usize + ship.length as usize),
+        };
+        if ship.length == 0 || end_x > grid_size.width || end_y > grid_size.height {
+            problems.push(format!("ship {} at ({}, {}) doesn't fit on the board", i, ship.x, ship.y));
+            revealed_cells.push(Vec::new());
+        } else {
+            revealed_cells.push(ship.cells());
+        }
+    }
+
+    // same placement rules as the setup circuit
+    for i in 0..revealed_cells.len() {
+        for j in (i + 1)..revealed_cells.len() {
+            let distance = revealed_cells[i]
+                .iter()
+                .flat_map(|a| revealed_cells[j].iter().map(move |b| (a, b)))
+                .map(|(a, b)| a.0.abs_diff(b.0).max(a.1.abs_diff(b.1)))
+                .min();
+            match (distance, adjacency) {
+                (Some(0), _) => problems.push(format!("ships {} and {} overlap", i, j)),
+                (Some(1), AdjacencyRule::Forbidden) => problems.push(format!("ships {} and {} touch", i, j)),
+                _ => {}
+            }
+        }
+    }
+
+    let mut occupied = vec![false; grid_size.cells()];
+    for &(x, y) in revealed_cells.iter().flatten() {
+        occupied[grid_size.index(x as usize, y as usize)] = true;
+    }
+
+    // the openings have to match the published commitments
+    match &reveal.cells {
+        None => {
+            if published.len() != reveal.ships.len() {
+                problems.push(format!(
+                    "revealed {} ships but {} commitments were published",
+                    reveal.ships.len(),
+                    published.len()
+                ));
+            }
+            for (i, ((ship, salt), commitment)) in reveal.ships.iter().zip(published.iter()).enumerate() {
+                if !Prover::verify_commitment(ship, *salt, *commitment) {
+                    problems.push(format!("ship {} doesn't open its published commitment", i));
+                }
+            }
+        }
+        Some((cells, salts)) => {
+            if cells.len() != grid_size.cells() || salts.len() != cells.len() {
+                problems.push("revealed board has the wrong number of cells".to_string());
+            } else {
+                let board = BoardCommitment::from_openings(grid_size, cells.clone(), salts.clone());
+                if published != [board.root] {
+                    problems.push("revealed cells don't open the published board root".to_string());
+                }
+                if *cells != occupied {
+                    problems.push("revealed ships don't match the revealed board cells".to_string());
+                }
+            }
+        }
+    }
+
+    // finally every answer given during the game has to agree with the revealed board
+    for &(x, y, hit) in answers {
+        let (x, y) = (x as usize, y as usize);
+        let actual = grid_size.contains(x, y) && occupied[grid_size.index(x, y)];
+        if actual != hit {
+            let answer = |hit: bool| if hit { "hit" } else { "miss" };
+            problems.push(format!(
+                "answered {} at ({}, {}) but the revealed board says {}",
+                answer(hit),
+                x,
+                y,
+                answer(actual)
+            ));
+        }
+    }
+
+    problems
+}
+
+// reveal phase for one side: it opens its commitments and the match audits them through the opponent's view
+fn report_audit(game_match: &mut Match, side: Side, reveal: &BoardReveal) {
+    let owner = game_match.name(side).to_string();
+    let answers = game_match.view(side).map_or(0, |view| view.answers.len());
+    match game_match.reveal(side, reveal) {
+        Ok(problems) if problems.is_empty() => println!(
+            "{} reveal audit passed: all commitments opened and all {} answers were honest.",
+            owner, answers
+        ),
+        Ok(problems) => {
+            println!("{} reveal audit FAILED:", owner);
+            for problem in problems {
+                println!("- {}", problem);
+            }
+        }
+        Err(e) => println!("{} reveal rejected: {}", owner, e),
+    }
+}
+
+// JSON transcript of a whole game for external auditors. the readable fields (hex field elements,
+// hex Ristretto points) are there for people, the base64 fields are what actually gets re-verified.
+// version 2 records the board as a width and a height
+const TRANSCRIPT_VERSION: u8 = 2;
+pub const TRANSCRIPT_PATH: &str = "transcript.json";
+
+#[derive(Serialize, Deserialize)]
+struct GameTranscript {
+    version: u8,
+    k: u32, // the params for this K are needed to re-verify
+    grid_size: GridSize,
+    fleet: Vec<TranscriptShip>,
+    adjacency: AdjacencyRule,
+    commitment_mode: CommitmentMode,
+    boards: Vec<TranscriptBoard>,
+    shots: Vec<TranscriptShot>, // every verified shot, in the order they were played
+    winner: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TranscriptShip {
+    name: String,
+    length: u8,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TranscriptBoard {
+    owner: String,
+    ship_commitments: Vec<String>,            // Poseidon commitments, hex
+    board_root: Option<String>,               // board commitment mode only, hex
+    coordinate_commitments: Vec<[String; 2]>, // Bulletproof Pedersen commitments to (x, y) of each ship, hex
+    published: String,                        // base64 of the PublishedBoard wire format
+    setup_proof: Option<String>,              // base64, fleet setup or board setup
+    link_proofs: Vec<String>,                 // base64, per ship mode only
+}
+
+#[derive(Serialize, Deserialize)]
+struct TranscriptShot {
+    attacker: String,
+    defender: String,
+    x: u8,
+    y: u8,
+    result: String, // "hit" or "miss", as claimed by the defender
+    proof: String,  // base64
+    sunk: Vec<TranscriptSunk>,
+    fleet_status: Option<TranscriptStatus>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TranscriptSunk {
+    ship: usize,
+    proof: String, // base64
+}
+
+#[derive(Serialize, Deserialize)]
+struct TranscriptStatus {
+    all_sunk: bool,
+    proof: String, // base64
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// field elements are written big endian with 0x in front, same as their Debug output
+fn fp_hex(value: &Fp) -> String {
+    let mut repr = value.to_repr();
+    repr.reverse();
+    format!("0x{}", to_hex(&repr))
+}
+
+fn from_base64(value: &str, what: &str) -> Result<Vec<u8>, String> {
+    BASE64.decode(value).map_err(|e| format!("{} is not valid base64: {}", what, e))
+}
+
+fn shot_result(hit: bool) -> String {
+    if hit { "hit" } else { "miss" }.to_string()
+}
+
+impl TranscriptBoard {
+    // readable version of a published board, the proofs get filled in as the game goes
+    fn new(owner: &str, board: &PublishedBoard) -> Self {
+        TranscriptBoard {
+            owner: owner.to_string(),
+            ship_commitments: board.ship_commitments.iter().map(fp_hex).collect(),
+            board_root: board.board_root.as_ref().map(fp_hex),
+            coordinate_commitments: board
+                .ship_range_proofs
+                .iter()
+                .map(|placement| [to_hex(placement.x.commitment.as_bytes()), to_hex(placement.y.commitment.as_bytes())])
+                .collect(),
+            published: BASE64.encode(board.to_bytes()),
+            setup_proof: None,
+            link_proofs: Vec::new(),
+        }
+    }
+}
+
+impl GameTranscript {
+    fn save(&self, path: &str) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| format!("couldn't write {}: {}", path, e))
+    }
+
+    fn load(path: &str) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path).map_err(|e| format!("couldn't read {}: {}", path, e))?;
+        // older versions have different fields, so their version is checked before the rest gets parsed
+        let version = serde_json::from_str::<serde_json::Value>(&json)
+            .ok()
+            .and_then(|value| value.get("version")?.as_u64());
+        if let Some(version) = version
+            && version != TRANSCRIPT_VERSION as u64
+        {
+            return Err(format!("{} has unsupported transcript version {}", path, version));
+        }
+        serde_json::from_str(&json).map_err(|e| format!("{} is not a valid transcript: {}", path, e))
+    }
+}
+
+// replays a transcript through a fresh match, so every message gets the same checks it got in the game.
+// returns the problems found, empty means the whole game checks out
+fn verify_transcript(transcript: &GameTranscript, params: &Params<EqAffine>) -> Vec<String> {
+    let mut problems = Vec::new();
+    if transcript.version != TRANSCRIPT_VERSION {
+        problems.push(format!("unsupported transcript version {}", transcript.version));
+        return problems;
+    }
+    if transcript.k != params_k(params) {
+        problems.push(format!(
+            "transcript was made with params for K = {} but the params given are for K = {}",
+            transcript.k,
+            params_k(params)
+        ));
+        return problems;
+    }
+    let [first_board, second_board] = transcript.boards.as_slice() else {
+        problems.push(format!("transcript has {} boards instead of 2", transcript.boards.len()));
+        return problems;
+    };
+
+    let rules = GameRules {
+        grid_size: transcript.grid_size,
+        fleet: transcript.fleet.iter().map(|ship| ship.length).collect(),
+        adjacency: transcript.adjacency,
+        commitment_mode: transcript.commitment_mode,
+    };
+    if let Err(e) = rules.check() {
+        problems.push(format!("transcript rules can't be played: {}", e));
+        return problems;
+    }
+    if required_k(&rules) != transcript.k {
+        problems.push(format!("the rules need params for K = {} but the transcript says K = {}", required_k(&rules), transcript.k));
+        return problems;
+    }
+    // verifying keys only depend on the circuit shape so they get regenerated from the stored params
+    let keys = initialize_verifying_keys(params, &rules);
+
+    // the first board recorded is the Player side, whoever fired the first shot moves first
+    let names = [first_board.owner.as_str(), second_board.owner.as_str()];
+    let side_of = |name: &str| names.iter().position(|&owner| owner == name).map(|index| [Side::Player, Side::Opponent][index]);
+    let first = transcript.shots.first().and_then(|shot| side_of(&shot.attacker)).unwrap_or(Side::Player);
+    let mut game_match = Match::new(params, &keys, rules, names, first);
+
+    // decode every published board and make sure the readable fields say the same thing
+    let mut setups = Vec::new();
+    for (side, board) in [(Side::Player, first_board), (Side::Opponent, second_board)] {
+        let published = match from_base64(&board.published, "published board")
+            .and_then(|bytes| PublishedBoard::from_bytes(&bytes).map_err(|e| e.to_string()))
+        {
+            Ok(published) => published,
+            Err(e) => {
+                problems.push(format!("{} board: {}", board.owner, e));
+                return problems;
+            }
+        };
+        let readable = TranscriptBoard::new(&board.owner, &published);
+        if readable.ship_commitments != board.ship_commitments
+            || readable.board_root != board.board_root
+            || readable.coordinate_commitments != board.coordinate_commitments
+        {
+            problems.push(format!("{} board readable commitments don't match the published board", board.owner));
+            return problems;
+        }
+        if let Err(e) = game_match.commit(side, published) {
+            problems.push(format!("{} board: {}", board.owner, e));
+            return problems;
+        }
+
+        let setup_proof = board.setup_proof.as_deref().map(|proof| from_base64(proof, "setup proof")).transpose();
+        let link_proofs: Result<Vec<_>, _> =
+            board.link_proofs.iter().map(|proof| from_base64(proof, "link proof")).collect();
+        match (setup_proof, link_proofs) {
+            (Ok(setup_proof), Ok(link_proofs)) => setups.push((side, setup_proof, link_proofs)),
+            (Err(e), _) | (_, Err(e)) => {
+                problems.push(format!("{} board: {}", board.owner, e));
+                return problems;
+            }
+        }
+    }
+    for (side, setup_proof, link_proofs) in setups {
+        if let Err(e) = game_match.setup(side, setup_proof, link_proofs) {
+            problems.push(format!("{} setup: {}", game_match.name(side), e));
+            return problems;
+        }
+    }
+
+    // replay the shots in order, each one is checked against the defender's public history so far
+    for (turn, shot) in transcript.shots.iter().enumerate() {
+        let (Some(attacker), Some(defender)) = (side_of(&shot.attacker), side_of(&shot.defender)) else {
+            problems.push(format!("shot {}: unknown player {} or {}", turn, shot.attacker, shot.defender));
+            return problems;
+        };
+        if let Err(e) = game_match.attack(attacker, Attack { x: shot.x, y: shot.y }) {
+            problems.push(format!("shot {}: {}", turn, e));
+            return problems;
+        }
+        let view = game_match.view(defender).expect("both boards are committed");
+        let answer = ShotAnswer::from_transcript(shot, view);
+        let result = answer.and_then(|answer| game_match.answer(defender, &answer).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            problems.push(format!("shot {}: {} at ({}, {}) rejected: {}", turn, shot.result, shot.x, shot.y, e));
+            return problems;
+        }
+    }
+
+    let winner = game_match.winner().map(|side| game_match.name(side).to_string());
+    if winner != transcript.winner {
+        problems.push(format!("transcript claims winner {:?} but the proofs say {:?}", transcript.winner, winner));
+    }
+
+    problems
 }
 
-// verifies the generated proof
-fn verify_proof_strat(
+// an attack at (x, y), the first thing sent every turn
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Attack {
+    x: u8,
+    y: u8,
+}
+
+impl Attack {
+    fn to_bytes(self) -> Vec<u8> {
+        let mut writer = WireWriter::new(WIRE_ATTACK);
+        writer.u8(self.x);
+        writer.u8(self.y);
+        writer.finish()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, WireError> {
+        let mut reader = WireReader::new(bytes, WIRE_ATTACK)?;
+        let attack = Attack { x: reader.u8()?, y: reader.u8()? };
+        reader.finish()?;
+        Ok(attack)
+    }
+}
+
+// first thing a referee sends each client: which seat it got, the host shoots first
+struct Seat {
+    host: bool,
+}
+
+impl Seat {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = WireWriter::new(WIRE_SEAT);
+        writer.u8(self.host as u8);
+        writer.finish()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, WireError> {
+        let mut reader = WireReader::new(bytes, WIRE_SEAT)?;
+        let seat = Seat { host: reader.bool()? };
+        reader.finish()?;
+        Ok(seat)
+    }
+}
+
+// what a side sends at setup: its published board and the proofs about it
+struct BoardSetup {
+    board: PublishedBoard,
+    setup_proof: Option<Vec<u8>>, // fleet setup, or board setup in board mode
+    link_proofs: Vec<Vec<u8>>,    // per ship mode only
+}
+
+impl BoardSetup {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = WireWriter::new(WIRE_BOARD_SETUP);
+        writer.blob(&self.board.to_bytes());
+        match &self.setup_proof {
+            Some(proof) => {
+                writer.u8(1);
+                writer.blob(proof);
+            }
+            None => writer.u8(0),
+        }
+        writer.u32(self.link_proofs.len());
+        for proof in &self.link_proofs {
+            writer.blob(proof);
+        }
+        writer.finish()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, WireError> {
+        let mut reader = WireReader::new(bytes, WIRE_BOARD_SETUP)?;
+        let board = PublishedBoard::from_bytes(reader.blob()?)?;
+        let setup_proof = match reader.bool()? {
+            true => Some(reader.blob()?.to_vec()),
+            false => None,
+        };
+        let count = reader.count(4)?;
+        let link_proofs = (0..count)
+            .map(|_| reader.blob().map(|proof| proof.to_vec()))
+            .collect::<Result<Vec<_>, _>>()?;
+        reader.finish()?;
+        Ok(BoardSetup { board, setup_proof, link_proofs })
+    }
+
+}
+
+// the defender's answer to an attack: the shot proof plus the sunk and fleet status proofs a hit comes with
+struct ShotAnswer {
+    shot: ShotProof,
+    sunk: Vec<(usize, Vec<u8>)>,          // (ship index, sunk proof) for every ship that just went down
+    fleet_status: Option<(bool, Vec<u8>)>, // (all sunk, proof) after a hit, per ship mode only
+}
+
+impl ShotAnswer {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = WireWriter::new(WIRE_SHOT_ANSWER);
+        writer.blob(&self.shot.to_bytes());
+        writer.u32(self.sunk.len());
+        for (index, proof) in &self.sunk {
+            writer.u32(*index);
+            writer.blob(proof);
+        }
+        match &self.fleet_status {
+            Some((all_sunk, proof)) => {
+                writer.u8(1);
+                writer.u8(*all_sunk as u8);
+                writer.blob(proof);
+            }
+            None => writer.u8(0),
+        }
+        writer.finish()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, WireError> {
+        let mut reader = WireReader::new(bytes, WIRE_SHOT_ANSWER)?;
+        let shot = ShotProof::from_bytes(reader.blob()?)?;
+        let count = reader.count(8)?;
+        let sunk = (0..count)
+            .map(|_| Ok((reader.u32()?, reader.blob()?.to_vec())))
+            .collect::<Result<Vec<_>, WireError>>()?;
+        let fleet_status = match reader.bool()? {
+            true => Some((reader.bool()?, reader.blob()?.to_vec())),
+            false => None,
+        };
+        reader.finish()?;
+        Ok(ShotAnswer { shot, sunk, fleet_status })
+    }
+
+    // transcript record of this answer
+    fn transcript_shot(&self, attacker: &str, defender: &str) -> TranscriptShot {
+        TranscriptShot {
+            attacker: attacker.to_string(),
+            defender: defender.to_string(),
+            x: self.shot.attack_x,
+            y: self.shot.attack_y,
+            result: shot_result(self.shot.hit),
+            proof: BASE64.encode(&self.shot.proof),
+            sunk: self
+                .sunk
+                .iter()
+                .map(|(ship, proof)| TranscriptSunk { ship: *ship, proof: BASE64.encode(proof) })
+                .collect(),
+            fleet_status: self
+                .fleet_status
+                .as_ref()
+                .map(|(all_sunk, proof)| TranscriptStatus { all_sunk: *all_sunk, proof: BASE64.encode(proof) }),
+        }
+    }
+
+    // answer back from its transcript record. the public inputs aren't stored in the transcript,
+    // they get rebuilt from the defender's published board
+    fn from_transcript(shot: &TranscriptShot, defender: &Verifier) -> Result<Self, String> {
+        let hit = match shot.result.as_str() {
+            "hit" => true,
+            "miss" => false,
+            other => return Err(format!("unknown result {}", other)),
+        };
+        let proof = from_base64(&shot.proof, "shot proof")?;
+        let sunk = shot
+            .sunk
+            .iter()
+            .map(|sunk| Ok((sunk.ship, from_base64(&sunk.proof, "sunk proof")?)))
+            .collect::<Result<Vec<_>, String>>()?;
+        let fleet_status = match &shot.fleet_status {
+            Some(status) => Some((status.all_sunk, from_base64(&status.proof, "fleet status proof")?)),
+            None => None,
+        };
+        let public_inputs = defender.shot_public_inputs(shot.x, shot.y, hit);
+        let shot = ShotProof { attack_x: shot.x, attack_y: shot.y, hit, proof, public_inputs };
+        Ok(ShotAnswer { shot, sunk, fleet_status })
+    }
+}
+
+impl BoardReveal {
+    // end of game openings, sent once the game is over
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = WireWriter::new(WIRE_BOARD_REVEAL);
+        writer.u32(self.ships.len());
+        for (ship, salt) in &self.ships {
+            writer.u8(ship.x);
+            writer.u8(ship.y);
+            writer.u8(match ship.orientation {
+                Orientation::Horizontal => 0,
+                Orientation::Vertical => 1,
+            });
+            writer.u8(ship.length);
+            writer.fixed(&salt.to_le_bytes());
+        }
+        match &self.cells {
+            Some((cells, salts)) => {
+                writer.u8(1);
+                writer.u32(cells.len());
+                for (occupied, salt) in cells.iter().zip(salts.iter()) {
+                    writer.u8(*occupied as u8);
+                    writer.fixed(&salt.to_le_bytes());
+                }
+            }
+            None => writer.u8(0),
+        }
+        writer.finish()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, WireError> {
+        let mut reader = WireReader::new(bytes, WIRE_BOARD_REVEAL)?;
+        let count = reader.count(12)?;
+        let mut ships = Vec::with_capacity(count);
+        for _ in 0..count {
+            let x = reader.u8()?;
+            let y = reader.u8()?;
+            let orientation = match reader.u8()? {
+                0 => Orientation::Horizontal,
+                1 => Orientation::Vertical,
+                other => return Err(WireError::Malformed(format!("unknown orientation {}", other))),
+            };
+            let length = reader.u8()?;
+            let salt = u64::from_le_bytes(reader.array()?);
+            ships.push((Ship { x, y, orientation, length }, salt));
+        }
+        let cells = match reader.bool()? {
+            true => {
+                let count = reader.count(9)?;
+                let mut cells = Vec::with_capacity(count);
+                let mut salts = Vec::with_capacity(count);
+                for _ in 0..count {
+                    cells.push(reader.bool()?);
+                    salts.push(u64::from_le_bytes(reader.array()?));
+                }
+                Some((cells, salts))
+            }
+            false => None,
+        };
+        reader.finish()?;
+        Ok(BoardReveal { ships, cells })
+    }
+}
+
+// networked two player mode: both sides run this binary, the host listens and the guest connects.
+// every message on the stream is one of the wire format messages above with a u32 length in front
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:7878";
+const REFEREE_TRANSCRIPT_PATH: &str = "referee_transcript.json";
+const MAX_FRAME_SIZE: usize = 16 * 1024 * 1024; // way more than the biggest setup message
+
+fn send_frame(stream: &mut impl Write, bytes: &[u8]) -> Result<(), String> {
+    stream
+        .write_all(&(bytes.len() as u32).to_le_bytes())
+        .and_then(|_| stream.write_all(bytes))
+        .and_then(|_| stream.flush())
+        .map_err(|e| format!("connection lost while sending: {}", e))
+}
+
+fn receive_frame(stream: &mut impl Read) -> Result<Vec<u8>, String> {
+    let mut len = [0u8; 4];
+    stream.read_exact(&mut len).map_err(|e| format!("connection lost while receiving: {}", e))?;
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_FRAME_SIZE {
+        return Err(format!("opponent sent a {} byte message, refusing it", len));
+    }
+    let mut bytes = vec![0u8; len];
+    stream.read_exact(&mut bytes).map_err(|e| format!("connection lost while receiving: {}", e))?;
+    Ok(bytes)
+}
+
+// receives one message of the expected kind, anything else (like a message out of turn) is an error
+fn receive_message<T>(
+    stream: &mut TcpStream,
+    decode: impl Fn(&[u8]) -> Result<T, WireError>,
+) -> Result<T, String> {
+    let bytes = receive_frame(stream)?;
+    decode(&bytes).map_err(|e| format!("bad message from opponent: {}", e))
+}
+
+// defender side of a shot: proves the answer and, on a hit, the sunk and fleet status proofs that go with it
+fn answer_shot(game: &mut Prover, params: &Params<EqAffine>, keys: &GameKeys, attack: Attack) -> Result<ShotAnswer, String> {
+    let shot = game.prove_shot(params, &keys.shot.0, attack.x, attack.y)?;
+    let mut sunk = Vec::new();
+    let mut fleet_status = None;
+    if shot.hit {
+        game.record_hit(attack.x, attack.y);
+        if let Some(sunk_keys) = &keys.sunk {
+            for index in game.newly_sunk_ships() {
+                let (pk, _) = &sunk_keys[&game.fleet[index]];
+                sunk.push((index, game.prove_sunk(params, pk, index)));
+                game.sunk_announced[index] = true;
+            }
+        }
+        if let Some((pk, _)) = &keys.status {
+            let (proof, all_sunk) = game.prove_fleet_status(params, pk);
+            fleet_status = Some((all_sunk, proof));
+        }
+    }
+    Ok(ShotAnswer { shot, sunk, fleet_status })
+}
+
+// attacker side of a shot: every proof in the answer has to check out against the defender's view.
+// the view is only updated once all of them do. returns whether the defender's fleet is now gone
+fn check_answer(
+    view: &mut Verifier,
     params: &Params<EqAffine>,
-    vk: &VerifyingKey<EqAffine>,
-    proof: &[u8],
-    public_inputs: &[Fp],
-) -> bool {
-    
-    let strategy = SingleVerifier::new(params);
-    let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(proof);
+    keys: &VerifyingKeys,
+    attack: Attack,
+    answer: &ShotAnswer,
+) -> Result<bool, String> {
+    let shot = &answer.shot;
+    if (shot.attack_x, shot.attack_y) != (attack.x, attack.y) {
+        return Err(format!("answer is for ({}, {}) instead of ({}, {})", shot.attack_x, shot.attack_y, attack.x, attack.y));
+    }
+    if !view.verify_shot(params, &keys.shot, shot) {
+        return Err("shot proof verification failed".to_string());
+    }
+    if !shot.hit && !answer.sunk.is_empty() {
+        return Err("a miss can't sink a ship".to_string());
+    }
 
-    verify_proof(
-        params,
-        vk,
-        strategy,
-        &[&[public_inputs]],
-        &mut transcript,
-    )
-    .is_ok()
+    // the sunk and status proofs are about the hits including this one
+    let mut hits = view.hits.clone();
+    if shot.hit && !hits.contains(&(attack.x, attack.y)) {
+        hits.push((attack.x, attack.y));
+    }
+
+    for (i, (index, proof)) in answer.sunk.iter().enumerate() {
+        if view.sunk.contains(index) || answer.sunk[..i].iter().any(|(earlier, _)| earlier == index) {
+            return Err(format!("ship {} was already reported sunk", index));
+        }
+        let verified = match (&keys.sunk, view.board.fleet.get(*index)) {
+            (Some(sunk_keys), Some(length)) => view.verify_sunk(params, &sunk_keys[length], proof, *index, &hits),
+            _ => false,
+        };
+        if !verified {
+            return Err(format!("sunk proof for ship {} verification failed", index));
+        }
+    }
+
+    let game_over = match (&keys.status, &answer.fleet_status) {
+        (Some(vk), Some((all_sunk, proof))) => {
+            if !view.verify_fleet_status(params, vk, proof, *all_sunk, &hits) {
+                return Err("fleet status proof verification failed".to_string());
+            }
+            *all_sunk
+        }
+        (Some(_), None) if shot.hit => return Err("a hit came without a fleet status proof".to_string()),
+        // board mode has no status proofs. its setup proof pins the number of occupied cells,
+        // so the fleet is gone once that many cells have been hit
+        _ => hits.len() == fleet_cells(&view.board.fleet),
+    };
+
+    view.record_answer(attack.x, attack.y, shot.hit);
+    view.sunk.extend(answer.sunk.iter().map(|(index, _)| *index));
+    Ok(game_over)
+}
+
+// which side of a match, seen from whoever is running it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Side {
+    Player,
+    Opponent,
+}
+
+impl Side {
+    fn other(self) -> Side {
+        match self {
+            Side::Player => Side::Opponent,
+            Side::Opponent => Side::Player,
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            Side::Player => 0,
+            Side::Opponent => 1,
+        }
+    }
+}
+
+// biggest fleet the rules allow, at this size the circuits need K = 13
+const MAX_SHIPS: usize = 16;
+const MAX_FLEET_CELLS: usize = 64;
+// the board setup circuit hashes every cell, a 12x12 board needs K = 14
+const MAX_BOARD_MODE_CELLS: usize = 144;
+
+// everything both sides have to agree on before a match starts
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct GameRules {
+    grid_size: GridSize,
+    fleet: Vec<u8>,
+    adjacency: AdjacencyRule,
+    commitment_mode: CommitmentMode,
+}
+
+impl GameRules {
+    // catches rules that can't be played before anything gets sized from them. rules also come from the
+    // other side (a host's board at the referee, a transcript) so the fleet is capped, the circuits grow
+    // with the square of the number of ship cells and a huge fleet would need huge params
+    fn check(&self) -> Result<(), String> {
+        let (width, height, fleet) = (self.grid_size.width, self.grid_size.height, &self.fleet);
+        for (name, size) in [("width", width), ("height", height)] {
+            if size == 0 || size > u8::MAX as usize + 1 {
+                return Err(format!("board {} has to be between 1 and 256, not {}", name, size));
+            }
+        }
+        if fleet.is_empty() || fleet.len() > MAX_SHIPS {
+            return Err(format!("the fleet needs between 1 and {} ships, not {}", MAX_SHIPS, fleet.len()));
+        }
+        if fleet_cells(fleet) > MAX_FLEET_CELLS {
+            return Err(format!("the fleet can have at most {} ship cells, not {}", MAX_FLEET_CELLS, fleet_cells(fleet)));
+        }
+        if let Some(&length) = fleet.iter().find(|&&length| length == 0 || length as usize > width.max(height)) {
+            return Err(format!("a ship of length {} doesn't fit on the {} board", length, self.grid_size));
+        }
+        if fleet_cells(fleet) > self.grid_size.cells() {
+            return Err(format!("{} ship cells don't fit on the {} board", fleet_cells(fleet), self.grid_size));
+        }
+        if self.commitment_mode == CommitmentMode::Board && self.grid_size.cells() > MAX_BOARD_MODE_CELLS {
+            return Err(format!(
+                "board commitment mode supports boards of up to {} cells, not {}",
+                MAX_BOARD_MODE_CELLS,
+                self.grid_size.cells()
+            ));
+        }
+        Ok(())
+    }
+
+    // the rules a published board was made for
+    fn of_board(board: &PublishedBoard) -> Self {
+        GameRules {
+            grid_size: board.grid_size,
+            fleet: board.fleet.clone(),
+            adjacency: board.adjacency,
+            commitment_mode: if board.board_root.is_some() { CommitmentMode::Board } else { CommitmentMode::PerShip },
+        }
+    }
+}
+
+// protocol state of a match. a turn starts without an attack, the attacker's message fills it in
+// and the defender's answer ends the turn
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MatchState {
+    AwaitingCommitments,
+    AwaitingSetupProofs,
+    PlayerTurn { attack: Option<Attack> },
+    OpponentTurn { attack: Option<Attack> },
+    AwaitingRevealing { winner: Side },
+    Finished { winner: Side },
+}
+
+// why a match turned a message down, the state never changes when this happens
+#[derive(Debug)]
+enum MatchError {
+    OutOfTurn { state: MatchState, from: Side, message: &'static str },
+    InvalidMessage(String), // against the rules, no proof needed to tell
+    InvalidProof(String),
+}
+
+impl std::fmt::Display for MatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatchError::OutOfTurn { state, from, message } => {
+                write!(f, "{:?} sent {} out of turn (match is in {:?})", from, message, state)
+            }
+            MatchError::InvalidMessage(what) => write!(f, "{}", what),
+            MatchError::InvalidProof(what) => write!(f, "{}", what),
+        }
+    }
+}
+
+// what an accepted answer did to the game
+struct ShotOutcome {
+    hit: bool,
+    sunk: Vec<usize>, // indices of the ships that just went down
+    game_over: bool,
+}
+
+// protocol engine for one match. it takes the messages of both sides in order, checks every one
+// against the state and what was published so far and only moves on when it checks out.
+// it only holds public data, so local, networked and replayed games all run through it
+struct Match<'a> {
+    params: &'a Params<EqAffine>,
+    keys: &'a VerifyingKeys,
+    rules: GameRules,
+    names: [String; 2],
+    first: Side,
+    state: MatchState,
+    views: [Option<Verifier>; 2], // each side's board as the other side sees it
+    setup_done: [bool; 2],
+    audits: [Option<Vec<String>>; 2],
+    transcript: GameTranscript, // every accepted message, ready to save
+}
+
+impl<'a> Match<'a> {
+    fn new(params: &'a Params<EqAffine>, keys: &'a VerifyingKeys, rules: GameRules, names: [&str; 2], first: Side) -> Self {
+        let transcript = GameTranscript {
+            version: TRANSCRIPT_VERSION,
+            k: params_k(params),
+            grid_size: rules.grid_size,
+            fleet: rules
+                .fleet
+                .iter()
+                .enumerate()
+                .map(|(index, &length)| TranscriptShip { name: ship_name(&rules.fleet, index), length })
+                .collect(),
+            adjacency: rules.adjacency,
+            commitment_mode: rules.commitment_mode,
+            boards: Vec::new(),
+            shots: Vec::new(),
+            winner: None,
+        };
+        Match {
+            params,
+            keys,
+            rules,
+            names: names.map(str::to_string),
+            first,
+            state: MatchState::AwaitingCommitments,
+            views: [None, None],
+            setup_done: [false, false],
+            audits: [None, None],
+            transcript,
+        }
+    }
+
+    fn name(&self, side: Side) -> &str {
+        &self.names[side.index()]
+    }
+
+    // `side`'s board as its opponent sees it, once it has been committed
+    fn view(&self, side: Side) -> Option<&Verifier> {
+        self.views[side.index()].as_ref()
+    }
+
+    fn winner(&self) -> Option<Side> {
+        match self.state {
+            MatchState::AwaitingRevealing { winner } | MatchState::Finished { winner } => Some(winner),
+            _ => None,
+        }
+    }
+
+    fn out_of_turn(&self, from: Side, message: &'static str) -> MatchError {
+        MatchError::OutOfTurn { state: self.state, from, message }
+    }
+
+    // whose turn it is and the attack made in it so far
+    fn turn(&self) -> Option<(Side, Option<Attack>)> {
+        match self.state {
+            MatchState::PlayerTurn { attack } => Some((Side::Player, attack)),
+            MatchState::OpponentTurn { attack } => Some((Side::Opponent, attack)),
+            _ => None,
+        }
+    }
+
+    fn turn_state(side: Side, attack: Option<Attack>) -> MatchState {
+        match side {
+            Side::Player => MatchState::PlayerTurn { attack },
+            Side::Opponent => MatchState::OpponentTurn { attack },
+        }
+    }
+
+    // `side`'s board in the transcript, boards are recorded in the order they were committed
+    fn transcript_board(&mut self, side: Side) -> &mut TranscriptBoard {
+        let name = self.names[side.index()].clone();
+        self.transcript
+            .boards
+            .iter_mut()
+            .find(|board| board.owner == name)
+            .expect("board is recorded when it is committed")
+    }
+
+    // a side publishes its board, it has to be for the agreed rules
+    fn commit(&mut self, from: Side, board: PublishedBoard) -> Result<(), MatchError> {
+        if self.state != MatchState::AwaitingCommitments || self.views[from.index()].is_some() {
+            return Err(self.out_of_turn(from, "commitments"));
+        }
+        let mode = if board.board_root.is_some() { CommitmentMode::Board } else { CommitmentMode::PerShip };
+        if board.grid_size != self.rules.grid_size
+            || board.fleet != self.rules.fleet
+            || board.adjacency != self.rules.adjacency
+            || mode != self.rules.commitment_mode
+        {
+            return Err(MatchError::InvalidMessage(
+                "board was published for different rules (board, fleet, adjacency or commitment mode)".to_string(),
+            ));
+        }
+
+        let record = TranscriptBoard::new(self.name(from), &board);
+        self.transcript.boards.push(record);
+        self.views[from.index()] = Some(Verifier::new(board));
+        if self.views.iter().all(Option::is_some) {
+            self.state = MatchState::AwaitingSetupProofs;
+        }
+        Ok(())
+    }
+
+    // a side's setup proofs: range proofs (already in the published board), fleet setup and links.
+    // board mode has no per ship commitments, its setup proof is for the board root
+    fn setup(&mut self, from: Side, setup_proof: Option<Vec<u8>>, link_proofs: Vec<Vec<u8>>) -> Result<(), MatchError> {
+        if self.state != MatchState::AwaitingSetupProofs || self.setup_done[from.index()] {
+            return Err(self.out_of_turn(from, "setup proofs"));
+        }
+        let view = self.views[from.index()].as_ref().expect("both boards are committed");
+
+        match (self.rules.commitment_mode, &self.keys.link) {
+            (CommitmentMode::PerShip, Some(link_vk)) => {
+                if !view.verify_ship_proofs() {
+                    return Err(MatchError::InvalidProof("ship placement range proof verification failed".to_string()));
+                }
+                let setup_ok = setup_proof
+                    .as_ref()
+                    .is_some_and(|proof| view.verify_fleet_setup(self.params, &self.keys.setup, proof));
+                if !setup_ok {
+                    return Err(MatchError::InvalidProof("fleet setup proof verification failed".to_string()));
+                }
+                if !view.verify_ship_links(self.params, link_vk, &link_proofs) {
+                    return Err(MatchError::InvalidProof("range proof link verification failed".to_string()));
+                }
+            }
+            _ => {
+                if !link_proofs.is_empty() {
+                    return Err(MatchError::InvalidMessage("board mode has no link proofs".to_string()));
+                }
+                let setup_ok = setup_proof
+                    .as_ref()
+                    .is_some_and(|proof| view.verify_board_setup(self.params, &self.keys.setup, proof));
+                if !setup_ok {
+                    return Err(MatchError::InvalidProof("board setup proof verification failed".to_string()));
+                }
+            }
+        }
+
+        let record = self.transcript_board(from);
+        record.setup_proof = setup_proof.as_ref().map(|proof| BASE64.encode(proof));
+        record.link_proofs = link_proofs.iter().map(|proof| BASE64.encode(proof)).collect();
+        self.setup_done[from.index()] = true;
+        if self.setup_done.iter().all(|&done| done) {
+            self.state = Self::turn_state(self.first, None);
+        }
+        Ok(())
+    }
+
+    // the side whose turn it is fires a shot at the other one's board
+    fn attack(&mut self, from: Side, attack: Attack) -> Result<(), MatchError> {
+        match self.turn() {
+            Some((side, None)) if side == from => {}
+            _ => return Err(self.out_of_turn(from, "an attack")),
+        }
+        let target = self.views[from.other().index()].as_ref().expect("both boards are committed");
+        target.check_attack(attack.x, attack.y).map_err(MatchError::InvalidMessage)?;
+        self.state = Self::turn_state(from, Some(attack));
+        Ok(())
+    }
+
+    // the defender answers the pending attack, every proof in the answer has to check out
+    fn answer(&mut self, from: Side, answer: &ShotAnswer) -> Result<ShotOutcome, MatchError> {
+        let (attacker, attack) = match self.turn() {
+            Some((attacker, Some(attack))) if attacker == from.other() => (attacker, attack),
+            _ => return Err(self.out_of_turn(from, "an answer")),
+        };
+        let view = self.views[from.index()].as_mut().expect("both boards are committed");
+        let game_over =
+            check_answer(view, self.params, self.keys, attack, answer).map_err(MatchError::InvalidProof)?;
+
+        let record = answer.transcript_shot(&self.names[attacker.index()], &self.names[from.index()]);
+        self.transcript.shots.push(record);
+        self.state = if game_over {
+            self.transcript.winner = Some(self.names[attacker.index()].clone());
+            MatchState::AwaitingRevealing { winner: attacker }
+        } else {
+            Self::turn_state(from, None)
+        };
+        Ok(ShotOutcome {
+            hit: answer.shot.hit,
+            sunk: answer.sunk.iter().map(|(index, _)| *index).collect(),
+            game_over,
+        })
+    }
+
+    // after the game a side opens its board, returns what the audit found wrong with it
+    fn reveal(&mut self, from: Side, reveal: &BoardReveal) -> Result<Vec<String>, MatchError> {
+        let MatchState::AwaitingRevealing { winner } = self.state else {
+            return Err(self.out_of_turn(from, "a reveal"));
+        };
+        if self.audits[from.index()].is_some() {
+            return Err(self.out_of_turn(from, "a reveal"));
+        }
+        let view = self.views[from.index()].as_ref().expect("both boards are committed");
+        let problems = view.audit(reveal);
+        self.audits[from.index()] = Some(problems.clone());
+        if self.audits.iter().all(Option::is_some) {
+            self.state = MatchState::Finished { winner };
+        }
+        Ok(problems)
+    }
+}
+
+// proves a side's setup for the match: fleet setup and link proofs, or the board setup proof in board mode
+fn prove_setup(game: &Prover, params: &Params<EqAffine>, keys: &GameKeys) -> (Option<Vec<u8>>, Vec<Vec<u8>>) {
+    match &keys.link {
+        Some((link_pk, _)) if game.board_commitment.is_none() => (
+            Some(game.prove_fleet_setup(params, &keys.setup.0)),
+            game.prove_ship_links(params, link_pk),
+        ),
+        _ => (Some(game.prove_board_setup(params, &keys.setup.0)), Vec::new()),
+    }
+}
+
+
+// answers an attack on a local board and hands the answer to the match,
+// encoded and decoded again like it would be sent over a network
+fn answer_locally(
+    game_match: &mut Match,
+    game: &mut Prover,
+    keys: &GameKeys,
+    defender: Side,
+    attack: Attack,
+) -> Result<ShotOutcome, MatchError> {
+    let answer = answer_shot(game, game_match.params, keys, attack).map_err(MatchError::InvalidMessage)?;
+    let answer = ShotAnswer::from_bytes(&answer.to_bytes()).map_err(|e| MatchError::InvalidMessage(e.to_string()))?;
+    game_match.answer(defender, &answer)
+}
+
+// sends our message and receives the opponent's one of the same kind. the host sends first
+// so the two never block on each other
+fn exchange<T>(
+    stream: &mut TcpStream,
+    host: bool,
+    bytes: &[u8],
+    decode: impl Fn(&[u8]) -> Result<T, WireError>,
+) -> Result<T, String> {
+    if host {
+        send_frame(stream, bytes)?;
+        receive_message(stream, decode)
+    } else {
+        let message = receive_message(stream, decode)?;
+        send_frame(stream, bytes)?;
+        Ok(message)
+    }
+}
+
+// one side of a networked game, the local board is the match's Player side. the host shoots first.
+// our own messages go through the match too so both instances walk through the same states
+fn play_networked(
+    mut stream: TcpStream,
+    host: bool,
+    mut game: Prover,
+    params: &Params<EqAffine>,
+    keys: &GameKeys,
+    view_opponent_hashes: bool,
+    save_transcript: bool,
+) -> Result<(), String> {
+    let (names, first) = if host { (["Host", "Guest"], Side::Player) } else { (["Guest", "Host"], Side::Opponent) };
+    let (setup_proof, link_proofs) = prove_setup(&game, params, keys);
+    let own_setup = BoardSetup { board: game.publish(), setup_proof, link_proofs };
+    let verifying_keys = keys.verifying();
+    let mut game_match = Match::new(params, &verifying_keys, GameRules::of_board(&own_setup.board), names, first);
+
+    let opponent_setup = exchange(&mut stream, host, &own_setup.to_bytes(), BoardSetup::from_bytes)?;
+    game_match
+        .commit(Side::Player, own_setup.board)
+        .map_err(|e| format!("Own setup rejected: {}", e))?;
+    game_match
+        .commit(Side::Opponent, opponent_setup.board)
+        .map_err(|e| format!("Opponent setup rejected: {}. Refusing to play.", e))?;
+    game_match
+        .setup(Side::Player, own_setup.setup_proof, own_setup.link_proofs)
+        .map_err(|e| format!("Own setup rejected: {}", e))?;
+    game_match
+        .setup(Side::Opponent, opponent_setup.setup_proof, opponent_setup.link_proofs)
+        .map_err(|e| format!("Opponent setup rejected: {}. Refusing to play.", e))?;
+    println!("Opponent board and setup proofs verified.");
+    if view_opponent_hashes && let Some(view) = game_match.view(Side::Opponent) {
+        view.print_commitments("Opponent");
+    }
+
+    loop {
+        match game_match.state {
+            MatchState::PlayerTurn { .. } => {
+                println!("\nYour turn");
+                let attack = Attack { x: get_input("Enter attack x-coordinate: "), y: get_input("Enter attack y-coordinate: ") };
+                if let Err(e) = game_match.attack(Side::Player, attack) {
+                    println!("Invalid attack: {}", e);
+                    continue;
+                }
+                send_frame(&mut stream, &attack.to_bytes())?;
+
+                let answer = receive_message(&mut stream, ShotAnswer::from_bytes)?;
+                let outcome = game_match
+                    .answer(Side::Opponent, &answer)
+                    .map_err(|e| format!("Opponent sent an invalid answer: {}", e))?;
+                println!("Attack verified with SNARKs!");
+                println!("\n{}", if outcome.hit { "Hit!" } else { "Miss!" });
+                for index in outcome.sunk {
+                    println!("Opponent's {} has been sunk! (sunk proof verified)", ship_name(&game_match.rules.fleet, index));
+                }
+                if outcome.game_over {
+                    println!("You win!");
+                }
+            }
+            MatchState::OpponentTurn { .. } => {
+                println!("\nOpponent attacking...");
+                let attack = receive_message(&mut stream, Attack::from_bytes)?;
+                game_match
+                    .attack(Side::Opponent, attack)
+                    .map_err(|e| format!("Opponent sent an invalid attack: {}", e))?;
+                let answer = answer_shot(&mut game, params, keys, attack)?;
+                send_frame(&mut stream, &answer.to_bytes())?;
+
+                let outcome = game_match.answer(Side::Player, &answer).map_err(|e| format!("Own answer rejected: {}", e))?;
+                println!("Opponent targeted ({}, {}): {}", attack.x, attack.y, if outcome.hit { "hit" } else { "miss" });
+                for index in outcome.sunk {
+                    println!("Your {} has been sunk!", ship_name(&game_match.rules.fleet, index));
+                }
+                if outcome.game_over {
+                    println!("Opponent wins!");
+                }
+            }
+            _ => break,
+        }
+    }
+
+    println!("\nYour board:");
+    game.print_grid();
+
+    // reveal phase: both sides open everything and check the other one's answers
+    println!("\nRevealing and auditing the opponent's board...");
+    let own_reveal = game.reveal();
+    let opponent_reveal = exchange(&mut stream, host, &own_reveal.to_bytes(), BoardReveal::from_bytes)?;
+    game_match.reveal(Side::Player, &own_reveal).map_err(|e| e.to_string())?;
+    report_audit(&mut game_match, Side::Opponent, &opponent_reveal);
+
+    if save_transcript {
+        match game_match.transcript.save(TRANSCRIPT_PATH) {
+            Ok(()) => println!("\nGame transcript saved to {} (check it with verify-transcript).", TRANSCRIPT_PATH),
+            Err(e) => println!("\nCould not save the game transcript: {}", e),
+        }
+    }
+    Ok(())
+}
+
+// referee side of one client message: receives it and decodes it, keeping the exact bytes to pass on
+fn referee_receive<T>(
+    stream: &mut TcpStream,
+    name: &str,
+    decode: impl Fn(&[u8]) -> Result<T, WireError>,
+) -> Result<(T, Vec<u8>), String> {
+    let bytes = receive_frame(stream).map_err(|e| format!("{}: {}", name, e))?;
+    let message = decode(&bytes).map_err(|e| format!("{} rejected: bad message: {}", name, e))?;
+    Ok((message, bytes))
+}
+
+// runs one match between two clients as the referee. the clients play exactly like in the two-player mode,
+// the referee sits in between, checks every message through its own match and only passes it on once it checks out.
+// it never sees a board, only what the clients publish, and the first client that sends something invalid is rejected
+fn referee_match(mut streams: [TcpStream; 2]) -> Result<(), String> {
+    let names = ["Host", "Guest"];
+    for (stream, host) in streams.iter_mut().zip([true, false]) {
+        send_frame(stream, &Seat { host }.to_bytes())?;
+    }
+    let rejected = |side: Side| move |e: MatchError| format!("{} rejected: {}", names[side.index()], e);
+
+    // the host's board decides the rules, the guest has to publish for the same ones
+    let (host_setup, host_bytes) = referee_receive(&mut streams[0], names[0], BoardSetup::from_bytes)?;
+    let rules = GameRules::of_board(&host_setup.board);
+    rules.check().map_err(|e| format!("Host rejected: {}", e))?;
+    println!("Host published a {} board, generating verifying keys for its rules...", rules.grid_size);
+    let params = initialize_params(required_k(&rules))?;
+    let keys = initialize_verifying_keys(&params, &rules);
+    let mut game_match = Match::new(&params, &keys, rules, names, Side::Player);
+
+    // setup proofs can only be checked once both boards are in, so the host's board goes to the guest first
+    game_match.commit(Side::Player, host_setup.board).map_err(rejected(Side::Player))?;
+    send_frame(&mut streams[1], &host_bytes)?;
+    let (guest_setup, guest_bytes) = referee_receive(&mut streams[1], names[1], BoardSetup::from_bytes)?;
+    game_match.commit(Side::Opponent, guest_setup.board).map_err(rejected(Side::Opponent))?;
+    game_match
+        .setup(Side::Player, host_setup.setup_proof, host_setup.link_proofs)
+        .map_err(rejected(Side::Player))?;
+    game_match
+        .setup(Side::Opponent, guest_setup.setup_proof, guest_setup.link_proofs)
+        .map_err(rejected(Side::Opponent))?;
+    send_frame(&mut streams[0], &guest_bytes)?;
+    println!("Both boards and setup proofs verified, the Host shoots first.");
+
+    while let Some((attacker, _)) = game_match.turn() {
+        let defender = attacker.other();
+        let (attack, bytes) =
+            referee_receive(&mut streams[attacker.index()], game_match.name(attacker), Attack::from_bytes)?;
+        game_match.attack(attacker, attack).map_err(rejected(attacker))?;
+        send_frame(&mut streams[defender.index()], &bytes)?;
+
+        let (answer, bytes) =
+            referee_receive(&mut streams[defender.index()], game_match.name(defender), ShotAnswer::from_bytes)?;
+        let outcome = game_match.answer(defender, &answer).map_err(rejected(defender))?;
+        send_frame(&mut streams[attacker.index()], &bytes)?;
+        println!(
+            "{} fired at ({}, {}): {} (proof verified)",
+            game_match.name(attacker),
+            attack.x,
+            attack.y,
+            if outcome.hit { "hit" } else { "miss" }
+        );
+        for index in outcome.sunk {
+            println!(
+                "{}'s {} has been sunk! (sunk proof verified)",
+                game_match.name(defender),
+                ship_name(&game_match.rules.fleet, index)
+            );
+        }
+    }
+
+    // the host reveals first like in the two-player mode, the guest gets it before sending its own
+    for side in [Side::Player, Side::Opponent] {
+        let (reveal, bytes) = referee_receive(&mut streams[side.index()], game_match.name(side), BoardReveal::from_bytes)?;
+        send_frame(&mut streams[side.other().index()], &bytes)?;
+        report_audit(&mut game_match, side, &reveal);
+    }
+    if let Some(winner) = game_match.winner() {
+        println!("\nReferee verdict: {} wins!", game_match.name(winner));
+    }
+
+    match game_match.transcript.save(REFEREE_TRANSCRIPT_PATH) {
+        Ok(()) => println!("Game transcript saved to {} (check it with verify-transcript).", REFEREE_TRANSCRIPT_PATH),
+        Err(e) => println!("Could not save the game transcript: {}", e),
+    }
+    Ok(())
 }
 
-// num of rows in curcuit which I think is 2^8 = 256 in this case (added more for poseidon hashing)
-const K: u32 = 8;
-const NUM_SHIPS: u8 = 3;
+// game setup from the command line and/or a JSON config file, e.g.
+// `--grid 8 --fleet 4,3,2 --adjacency allowed` or `--config game.json` with
+// {"width": 8, "height": 8, "fleet": [4, 3, 2], "adjacency": "allowed", "commitment_mode": "per_ship"}.
+// anything left out falls back to the classic 10x10 game, and rules that aren't set get asked for at startup
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GameConfig {
+    width: Option<usize>,
+    height: Option<usize>,
+    fleet: Option<Vec<u8>>,
+    adjacency: Option<AdjacencyRule>,
+    commitment_mode: Option<CommitmentMode>,
+}
+
+pub const CONFIG_USAGE: &str = "Game options:
+  --config FILE         read the options below from a JSON file (flags given after it override it)
+  --grid N              N x N board (same as --width N --height N)
+  --width N, --height N board dimensions, 1 to 256 each
+  --fleet 5,4,3,3,2     ship lengths, placed in this order
+  --adjacency RULE      allowed or forbidden (ships touching)
+  --commitment MODE     per_ship or board (board mode is for boards of up to 144 cells)";
+
+impl GameConfig {
+    // parses the game options, a --config file is applied where it appears so later flags win
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut config = GameConfig::default();
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let value = args.next().ok_or(format!("{} needs a value", flag))?;
+            match flag.as_str() {
+                "--config" => config.merge(GameConfig::load(value)?),
+                "--grid" => {
+                    let size = parse_dimension(flag, value)?;
+                    config.width = Some(size);
+                    config.height = Some(size);
+                }
+                "--width" => config.width = Some(parse_dimension(flag, value)?),
+                "--height" => config.height = Some(parse_dimension(flag, value)?),
+                "--fleet" => {
+                    let fleet = value
+                        .split(',')
+                        .map(|length| length.trim().parse::<u8>())
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|_| format!("--fleet takes ship lengths like 5,4,3,3,2, not {}", value))?;
+                    config.fleet = Some(fleet);
+                }
+                "--adjacency" => config.adjacency = Some(parse_option_value(flag, value)?),
+                "--commitment" => config.commitment_mode = Some(parse_option_value(flag, value)?),
+                _ => return Err(format!("unknown option {}", flag)),
+            }
+        }
+        config.check()?;
+        Ok(config)
+    }
+
+    fn load(path: &str) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path).map_err(|e| format!("Could not read config {}: {}", path, e))?;
+        serde_json::from_str(&json).map_err(|e| format!("{} is not a valid game config: {}", path, e))
+    }
 
-// generates the default params and writes them to a file
-fn write_params() {
-    let mut params_file = File::create("params.bin").unwrap();
-    let params: Params<EqAffine> = Params::new(K);
-    params.write(&mut params_file).unwrap();
-}
+    // options set in `other` replace ours
+    fn merge(&mut self, other: GameConfig) {
+        self.width = other.width.or(self.width);
+        self.height = other.height.or(self.height);
+        self.fleet = other.fleet.or(self.fleet.take());
+        self.adjacency = other.adjacency.or(self.adjacency);
+        self.commitment_mode = other.commitment_mode.or(self.commitment_mode);
+    }
 
-// initializes the params and keys
-fn initialize_params() -> (
-    Params<EqAffine>,
-    ProvingKey<EqAffine>,
-    VerifyingKey<EqAffine>
-) {
-    // file path for params
-    let params_path = "params.bin";
-
-    // reads params bin
-    let params_file = match File::open(params_path) {
-        Ok(file) => file,
-        Err(_) => {
-            println!("\nParams file not found. Please generate parameters first.");
-            println!("Generating parameters now...");
-            write_params();
-            println!("Parameters generated successfully!\n");
-            File::open(params_path).expect("Failed to open newly created params file")
-        }
-    };
+    fn width(&self) -> usize {
+        self.width.unwrap_or(10)
+    }
 
-    let params = Params::<EqAffine>::read(&mut BufReader::new(params_file)).expect("Failed to read params");
+    fn height(&self) -> usize {
+        self.height.unwrap_or(10)
+    }
 
-    let mut dummy_ships = Vec::new();
-    let mut dummy_commitments = Vec:: new();
-    
-    for _ in 0..NUM_SHIPS { // fixed fleet size of 3 ships
-        dummy_ships.push((
-            Value::unknown(),
-            Value::unknown(),
-            Value:: unknown(),
-        ));
-        dummy_commitments.push(Value::unknown());
+    fn fleet(&self) -> Vec<u8> {
+        self.fleet.clone().unwrap_or_else(default_fleet_lengths)
     }
-    
-    let empty_circuit = BattleshipCircuit {
-        ships: dummy_ships,  // had to switch to NUM_SHIPS (3) because not having a fixed size caused issues with keygen not sure why
-        published_commitments: dummy_commitments,
-        attack_x: Value::unknown(),
-        attack_y: Value::unknown(),
-        hit: Value::unknown(),
-    };
 
-    // generates proving and verifying keys
-    let vk = keygen_vk(&params, &empty_circuit).expect("Failed to generate verifying key");
-    let pk = keygen_pk(&params, vk.clone(), &empty_circuit).expect( "Failed to generate proving key");
+    // catches configs that can't be played before any keys get generated
+    fn check(&self) -> Result<(), String> {
+        // rules that are still to be asked for can't make a config unplayable, any value does for the check
+        GameRules {
+            grid_size: GridSize { width: self.width(), height: self.height() },
+            fleet: self.fleet(),
+            adjacency: self.adjacency.unwrap_or(AdjacencyRule::Allowed),
+            commitment_mode: self.commitment_mode.unwrap_or(CommitmentMode::PerShip),
+        }
+        .check()
+    }
+}
+
+fn parse_dimension(flag: &str, value: &str) -> Result<usize, String> {
+    value.parse().map_err(|_| format!("{} takes a number, not {}", flag, value))
+}
 
-    (params, pk, vk)
+// rule values are spelled the same as in the config file
+fn parse_option_value<T: serde::de::DeserializeOwned>(flag: &str, value: &str) -> Result<T, String> {
+    serde_json::from_value(serde_json::Value::String(value.to_string()))
+        .map_err(|_| format!("{} doesn't take {}", flag, value))
 }
 
-pub fn run() {
+pub fn run(config: &GameConfig) {
 
     println!("Welcome to Zattleship!");
     
     let input = get_input("Enter 1 to play the game or 2 to generate new parameters");
+    let opponent = get_opponent("Play against 0=the computer, 1=a TCP opponent (host), 2=a TCP opponent (join), 3=an opponent through a referee (join): ");
 
     let verbose_mode = get_bool("Enable verbose game output? (1=yes, 0=no): ");
     let view_opponent_hashes = get_bool(
         "View opponent's committed hashes after setup? (1=yes, 0=no): ",
     );
+    let adjacency = config.adjacency.unwrap_or_else(|| {
+        if get_bool("Allow ships to touch each other? (1=yes, 0=no): ") {
+            AdjacencyRule::Allowed
+        } else {
+            AdjacencyRule::Forbidden
+        }
+    });
+    let commitment_mode = config.commitment_mode.unwrap_or_else(|| {
+        // too big for the board setup circuit, so there is nothing to ask
+        if config.width() * config.height() > MAX_BOARD_MODE_CELLS {
+            return CommitmentMode::PerShip;
+        }
+        if get_bool("Commit to the whole board as one Merkle root instead of per ship? (1=yes, 0=no): ") {
+            CommitmentMode::Board
+        } else {
+            CommitmentMode::PerShip
+        }
+    });
+    let save_transcript = get_bool(
+        "Save a JSON transcript of the game for auditors? (1=yes, 0=no): ",
+    );
+
+    let grid_size = GridSize { width: config.width(), height: config.height() };
+    let fleet = config.fleet();
+    let rules = GameRules { grid_size, fleet: fleet.clone(), adjacency, commitment_mode };
+    let k = required_k(&rules);
 
     if input == 2 {
-        write_params();
-        println!("Parameters generated successfully!\n");
+        write_params(&params_path(k), k);
+        println!("Parameters for K = {} generated successfully!\n", k);
     }
 
     // Initialize parameters and keys
-    let (params, pk, vk) = initialize_params();
+    let params = match initialize_params(k) {
+        Ok(params) => params,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    let keys = initialize_game_keys(&params, &rules);
 
-    let grid_size = 10u8;
-    let num_ships = NUM_SHIPS;
-    println!("Using a fixed fleet size of {} ships", num_ships);
+    println!("Playing on a {} board with a fleet of {} ships:", grid_size, fleet.len());
+    for (index, length) in fleet.iter().enumerate() {
+        println!("- {} (length {})", ship_name(&fleet, index), length);
+    }
 
-    let mut player_game = BattleshipGame::new(grid_size as usize);
-    let mut computer_game = BattleshipGame::new(grid_size as usize);
+    // networked mode: the opponent is another instance of this binary instead of the computer
+    if opponent != Opponent::Computer {
+        let address = get_text(&format!("Enter the address to use (empty for {}): ", DEFAULT_ADDRESS));
+        let address = if address.is_empty() { DEFAULT_ADDRESS.to_string() } else { address };
+        let stream = match opponent {
+            Opponent::Host => {
+                println!("Waiting for the opponent to connect on {}...", address);
+                TcpListener::bind(&address).and_then(|listener| listener.accept()).map(|(stream, _)| stream)
+            }
+            _ => TcpStream::connect(&address),
+        };
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                println!("Could not connect: {}", e);
+                return;
+            }
+        };
+        println!("Connected to the opponent.");
+
+        // through a referee the seat decides who plays first
+        let host = match opponent {
+            Opponent::Referee => match receive_message(&mut stream, Seat::from_bytes) {
+                Ok(seat) => seat.host,
+                Err(e) => {
+                    println!("{}", e);
+                    return;
+                }
+            },
+            _ => opponent == Opponent::Host,
+        };
 
-    // Player ship placement
-    println!("\nPlayer placing ships...");
-    for _ in 0..num_ships {
-        let ship_x = get_input("Enter the ship's x-coordinate: ");
-        let ship_y = get_input("Enter the ship's y-coordinate: ");
-        if let Err(e) = player_game.place_ship(ship_x, ship_y) {
-            println!("Invalid ship placement: {}", e);
-            continue;
+        let mut game = Prover::new(grid_size, &fleet, adjacency);
+        place_player_ships(&mut game, verbose_mode);
+        if commitment_mode == CommitmentMode::Board {
+            game.commit_board();
         }
-        if verbose_mode {
-            println!("Player committed ship at ({}, {})", ship_x, ship_y);
+        if let Err(e) = play_networked(stream, host, game, &params, &keys, view_opponent_hashes, save_transcript) {
+            println!("{}", e);
         }
+        return;
     }
 
-    // Demonstrate verifier-side check of player's published commitments + proofs
-    if player_game.verify_published_ship_proofs() {
-        println!("Player ship placement proofs verified against grid bound.");
-    } else {
-        println!("Player ship placement proof verification FAILED.");
-    }
+    let mut player_game = Prover::new(grid_size, &fleet, adjacency);
+    let mut computer_game = Prover::new(grid_size, &fleet, adjacency);
+
+    // Player ship placement
+    place_player_ships(&mut player_game, verbose_mode);
 
     // Computer ship placement
     println!("\nComputer placing ships...");
-    for _ in 0..num_ships {
-        let x = rand::thread_rng().gen_range(0..grid_size) as u8;
-        let y = rand::thread_rng().gen_range(0..grid_size) as u8;
+    let mut failed_attempts = 0;
+    let mut restarts = 0;
+    while let Some(length) = computer_game.next_ship_length() {
+        // random placement can paint itself into a corner (mostly with the no touching rule), start over
+        if failed_attempts > 1000 {
+            // a configured fleet can be too crowded to ever fit
+            restarts += 1;
+            if restarts > 100 {
+                println!("The computer couldn't fit its fleet on the board, try a smaller fleet or allow ships to touch.");
+                return;
+            }
+            computer_game = Prover::new(grid_size, &fleet, adjacency);
+            failed_attempts = 0;
+        }
 
-        println!("Computer's ship at: ({}, {})", x, y); // for debugging... reveals computer ship positions
-        
-        if computer_game.place_ship(x, y).is_err() {
-            // if placement fails try again
+        let x = rand::thread_rng().gen_range(0..grid_size.width) as u8;
+        let y = rand::thread_rng().gen_range(0..grid_size.height) as u8;
+        let orientation = if rand::thread_rng().gen_bool(0.5) {
+            Orientation::Horizontal
+        } else {
+            Orientation::Vertical
+        };
+
+        if computer_game.place_ship(x, y, length, orientation).is_err() {
+            // if placement fails (e.g. off the board or overlapping) try again
+            failed_attempts += 1;
             continue;
         }
 
+        println!("Computer's ship at: ({}, {}) {:?}", x, y, orientation); // for debugging... reveals computer ship positions
+
         if verbose_mode {
-            println!("Computer committed ship at ({}, {})", x, y);
+            println!("Computer committed ship of length {} at ({}, {})", length, x, y);
         }
         
     }
 
-    // Demonstrate verifier-side check of published commitments + proofs
-    if computer_game.verify_published_ship_proofs() {
-        println!("Computer ship placement proofs verified against grid bound.");
-    } else {
-        println!("Computer ship placement proof verification FAILED.");
+    if commitment_mode == CommitmentMode::Board {
+        // only the roots are published, each side proves its root covers the fleet's number of ship cells
+        player_game.commit_board();
+        computer_game.commit_board();
+        println!("Both boards committed as Poseidon Merkle roots.");
+    }
+
+    // the match engine checks every message from both sides against the rules and what was published so far.
+    // boards and answers go through the wire format like they would over a network
+    let verifying_keys = keys.verifying();
+    let mut game_match = Match::new(&params, &verifying_keys, rules, ["Player", "Computer"], Side::Player);
+
+    for (side, game) in [(Side::Player, &player_game), (Side::Opponent, &computer_game)] {
+        let published = PublishedBoard::from_bytes(&game.publish().to_bytes())
+            .map_err(|e| MatchError::InvalidMessage(e.to_string()))
+            .and_then(|board| game_match.commit(side, board));
+        if let Err(e) = published {
+            println!("{} published board rejected: {}. Refusing to play.", game_match.name(side), e);
+            return;
+        }
+    }
+
+    // each side proves its whole fleet is valid once and the opponent checks it before the first shot
+    // (range proofs against the grid bound, fleet setup and the links between the two)
+    for (side, game) in [(Side::Player, &player_game), (Side::Opponent, &computer_game)] {
+        let (setup_proof, link_proofs) = prove_setup(game, &params, &keys);
+        match game_match.setup(side, setup_proof, link_proofs) {
+            Ok(()) if commitment_mode == CommitmentMode::PerShip => {
+                println!("{} setup proofs verified (ships on the board and placed by the rules).", game_match.name(side));
+            }
+            Ok(()) => {
+                println!("{} board setup proof verified (the root covers {} ship cells).", game_match.name(side), fleet_cells(&fleet));
+            }
+            Err(e) => {
+                println!("{} setup proofs rejected: {}. Refusing to play.", game_match.name(side), e);
+                return;
+            }
+        }
     }
 
     if view_opponent_hashes {
-        computer_game.print_commitments("Computer");
-        player_game.print_commitments("Player");
+        for (side, label) in [(Side::Opponent, "Computer"), (Side::Player, "Player")] {
+            if let Some(view) = game_match.view(side) {
+                view.print_commitments(label);
+            }
+        }
     }
 
     loop {
@@ -905,149 +5837,77 @@ pub fn run() {
         // gets player's public inputs
 
         println!("\nPlayer Attacking");
-        let attack_x = get_input("Enter attack x-coordinate: ");
-        let attack_y = get_input("Enter attack y-coordinate: ");
-
-        match computer_game.verify_attack_range(attack_x, attack_y) {
-            Ok(hit) => {
-                // For both hits and misses, create SNARK proof to verify validity
-                // Get all ship commitments available to the circuit
-                let mut ships = Vec::new();
-                let mut commitments = Vec::new();
-                
-                for (i, ship_commitment) in computer_game.ship_commitments.iter().enumerate() {
-                    let (ship_x, ship_y) = computer_game.ship_positions[i];
-                    ships.push((
-                        Value::known(Fp::from(ship_x as u64)),
-                        Value::known(Fp::from(ship_y as u64)),
-                        Value::known(Fp::from(ship_commitment.salt)),
-                    ));
-                    commitments.push(Value::known(ship_commitment.commitment));
-                }
-
-                let commitments_count = commitments.len();
-                let circuit = BattleshipCircuit {
-                    ships,
-                    published_commitments: commitments,
-                    attack_x: Value::known(Fp::from(attack_x as u64)),
-                    attack_y: Value::known(Fp::from(attack_y as u64)),
-                    hit: Value::known(Fp::from(if hit { 1u64 } else { 0u64 })),
-                };
-
-                // Public inputs including hit/miss result
-                let public_inputs = vec![
-                    Fp::from(if hit { 1u64 } else { 0u64 }),
-                ];
-
-                // generates the proof
-                let proof = generate_proof(&params, &pk, circuit, &public_inputs);
+        let attack = Attack { x: get_input("Enter attack x-coordinate: "), y: get_input("Enter attack y-coordinate: ") };
+        if let Err(e) = game_match.attack(Side::Player, attack) {
+            println!("Invalid attack: {}", e);
+            continue;
+        }
 
-                // verifies the proof
-                if verify_proof_strat(&params, &vk, &proof, &public_inputs) {
-                    println!("Attack verified with SNARKs!");
-                    if verbose_mode {
-                        println!("Player attack commitments count: {}", commitments_count);
+        // the computer answers from its private board, the match checks the proofs through its view
+        match answer_locally(&mut game_match, &mut computer_game, &keys, Side::Opponent, attack) {
+            Ok(outcome) => {
+                println!("Attack verified with SNARKs!");
+                if verbose_mode && let Some(view) = game_match.view(Side::Opponent) {
+                    println!("Player attack commitments count: {}", view.commitments_count());
+                }
+                if outcome.hit {
+                    println!("\nHit!");
+                    for index in outcome.sunk {
+                        println!("Computer's {} has been sunk! (sunk proof verified)", ship_name(&fleet, index));
                     }
-                    if hit {
-                        println!("\nHit!");
-                        // record the hit on the computer's grid
-                        computer_game.record_hit(attack_x, attack_y);
-                        
-                        // check if all ships are sunk
-                        if computer_game.all_ships_sunk() {
-                            println!("You win!");
-                            break;
-                        }
-                    } else {
-                        println!("\nMiss!");
+                    if outcome.game_over {
+                        println!("You win!");
+                        break;
                     }
                 } else {
-                    println!("Invalid attack! Proof verification failed...");
-                    if verbose_mode {
-                        println!("Proof validation failed for player attack ({}, {})", attack_x, attack_y);
-                    }
+                    println!("\nMiss!");
                 }
             }
             Err(e) => {
-                println!("Invalid attack: {}", e);
-                continue;
+                println!("Invalid answer! {}. Ending the game.", e);
+                return;
             }
         }
 
         // computer's turn... this repeats the player process above
         // generates a random attack instead of asking for input
-        let attack_x = rand::thread_rng().gen_range(0..grid_size) as u8;
-        let attack_y = rand::thread_rng().gen_range(0..grid_size) as u8;
+        let attack = Attack {
+            x: rand::thread_rng().gen_range(0..grid_size.width) as u8,
+            y: rand::thread_rng().gen_range(0..grid_size.height) as u8,
+        };
 
         println!("\nComputer Attacking");
-        // println!("Computer's attack: ({}, {})", attack_x, attack_y); // for debugging... gives the player a chance to see the attack like in real battleship
-
-        match player_game.verify_attack_range(attack_x, attack_y) {
-            Ok(hit) => {
-                // For both hits and misses, create SNARK proof to verify validity
-                // Get all ship commitments available to the circuit
-                let mut ships = Vec::new();
-                let mut commitments = Vec::new();
-                
-                for (i, ship_commitment) in player_game.ship_commitments.iter().enumerate() {
-                    let (ship_x, ship_y) = player_game.ship_positions[i];
-                    ships.push((
-                        Value::known(Fp::from(ship_x as u64)),
-                        Value::known(Fp::from(ship_y as u64)),
-                        Value::known(Fp::from(ship_commitment.salt)),
-                    ));
-                    commitments.push(Value::known(ship_commitment.commitment));
-                }
-
-                let commitments_count = commitments.len();
-                let circuit = BattleshipCircuit {
-                    ships,
-                    published_commitments: commitments,
-                    attack_x: Value::known(Fp::from(attack_x as u64)),
-                    attack_y: Value::known(Fp::from(attack_y as u64)),
-                    hit: Value::known(Fp::from(if hit { 1u64 } else { 0u64 })),
-                };
-
-                // Public inputs including hit/miss result
-                let public_inputs = vec![
-                    Fp::from(if hit { 1u64 } else { 0u64 }),
-                ];
+        // println!("Computer's attack: ({}, {})", attack.x, attack.y); // for debugging... gives the player a chance to see the attack like in real battleship
 
-                // generates the proof
-                let proof = generate_proof(&params, &pk, circuit, &public_inputs);
+        if let Err(e) = game_match.attack(Side::Opponent, attack) {
+            println!("Invalid attack: {}. Ending the game.", e);
+            return;
+        }
 
-                // verifies the proof
-                if verify_proof_strat(&params, &vk, &proof, &public_inputs) {
-                    println!("Attack verified with SNARKs!");
-                    if verbose_mode {
-                        println!("Computer attack commitments count: {}", commitments_count);
-                        println!("Computer targeted ({}, {})", attack_x, attack_y);
+        match answer_locally(&mut game_match, &mut player_game, &keys, Side::Player, attack) {
+            Ok(outcome) => {
+                println!("Attack verified with SNARKs!");
+                if verbose_mode && let Some(view) = game_match.view(Side::Player) {
+                    println!("Computer attack commitments count: {}", view.commitments_count());
+                    println!("Computer targeted ({}, {})", attack.x, attack.y);
+                }
+                if outcome.hit {
+                    println!("\nHit!");
+                    for index in outcome.sunk {
+                        println!("Your {} has been sunk! (sunk proof verified)", ship_name(&fleet, index));
                     }
-                    if hit {
-                        println!("\nHit!");
-                        // record the hit on the player's grid
-                        player_game.record_hit(attack_x, attack_y);
-                        
-                        // check if all ships are sunk
-                        if player_game.all_ships_sunk() {
-                            println!("Computer wins!");
-                            break;
-                        }
-                    } else {
-                        println!("\nMiss!");
+                    if outcome.game_over {
+                        println!("Computer wins!");
+                        break;
                     }
                 } else {
-                    println!("Invalid attack! Proof verification failed...");
-                    if verbose_mode {
-                        println!("Proof validation failed for computer attack ({}, {})", attack_x, attack_y);
-                    }
+                    println!("\nMiss!");
                 }
             }
             Err(e) => {
-                println!("Invalid attack: {}", e);
-                continue;
+                println!("Invalid answer! {}. Ending the game.", e);
+                return;
             }
-
         }
 
     }
@@ -1057,6 +5917,121 @@ pub fn run() {
     println!("\nComputer's board:");
     computer_game.print_grid();
 
+    // reveal phase: both sides open everything they committed to and each answer they gave gets checked
+    println!("\nRevealing and auditing both boards...");
+    report_audit(&mut game_match, Side::Player, &player_game.reveal());
+    report_audit(&mut game_match, Side::Opponent, &computer_game.reveal());
+
+    if save_transcript {
+        match game_match.transcript.save(TRANSCRIPT_PATH) {
+            Ok(()) => println!("\nGame transcript saved to {} (check it with verify-transcript).", TRANSCRIPT_PATH),
+            Err(e) => println!("\nCould not save the game transcript: {}", e),
+        }
+    }
+
+}
+
+// verify-transcript entry point: reloads a saved game and re-verifies every proof in it
+// with the params file for the transcript's K
+pub fn verify_transcript_file(path: &str) {
+    let transcript = match GameTranscript::load(path) {
+        Ok(transcript) => transcript,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+
+    let params_file = params_path(transcript.k);
+    let params = match read_params(&params_file, transcript.k) {
+        Ok(params) => params,
+        Err(ParamsError::Missing) => {
+            println!("{} not found, it is needed to re-verify the proofs.", params_file);
+            return;
+        }
+        Err(e) => {
+            println!("Can't use {}: {}", params_file, e);
+            return;
+        }
+    };
+
+    println!(
+        "Verifying transcript {} ({} shots, {} board)...",
+        path,
+        transcript.shots.len(),
+        transcript.grid_size
+    );
+    let problems = verify_transcript(&transcript, &params);
+    if problems.is_empty() {
+        println!("Transcript verified: every proof checks out.");
+        if let Some(winner) = &transcript.winner {
+            println!("Winner: {}", winner);
+        }
+    } else {
+        println!("Transcript verification FAILED:");
+        for problem in problems {
+            println!("- {}", problem);
+        }
+    }
+}
+
+// referee entry point: waits for two clients on `address` and referees one match between them
+pub fn run_referee(address: &str) {
+    let listener = match TcpListener::bind(address) {
+        Ok(listener) => listener,
+        Err(e) => {
+            println!("Could not listen on {}: {}", address, e);
+            return;
+        }
+    };
+
+    println!("Referee waiting for two players on {}...", address);
+    let mut streams = Vec::new();
+    for name in ["Host", "Guest"] {
+        match listener.accept() {
+            Ok((stream, peer)) => {
+                println!("{} connected from {}.", name, peer);
+                streams.push(stream);
+            }
+            Err(e) => {
+                println!("Could not accept a player: {}", e);
+                return;
+            }
+        }
+    }
+
+    let streams: [TcpStream; 2] = streams.try_into().expect("two players connected");
+    if let Err(e) = referee_match(streams) {
+        println!("{}. Match over.", e);
+    }
+}
+
+// who the player is up against
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Opponent {
+    Computer,
+    Host,  // listens for another instance of this binary
+    Guest, // connects to one
+    Referee, // connects to a referee, which pairs it with another client
+}
+
+// places the local player's fleet from terminal input
+fn place_player_ships(game: &mut Prover, verbose_mode: bool) {
+    println!("\nPlayer placing ships...");
+    while let Some(length) = game.next_ship_length() {
+        let name = ship_name(&game.fleet, game.ships.len());
+        println!("Placing your {} (length {})", name, length);
+        let ship_x = get_input("Enter the ship's x-coordinate: ");
+        let ship_y = get_input("Enter the ship's y-coordinate: ");
+        let orientation = get_orientation("Enter the ship's orientation (0=horizontal, 1=vertical): ");
+        if let Err(e) = game.place_ship(ship_x, ship_y, length, orientation) {
+            println!("Invalid ship placement: {}", e);
+            continue;
+        }
+        if verbose_mode {
+            println!("Player committed {} at ({}, {}) {:?}", name, ship_x, ship_y, orientation);
+        }
+    }
 }
 
 // couldn't figure out a better way for getting input in rust from terminal
@@ -1068,6 +6043,38 @@ fn get_input(prompt: &str) -> u8 {
     input.trim().parse().unwrap() // everthing under the sun to get rid of unwanted inputs fr
 }
 
+fn get_orientation(prompt: &str) -> Orientation {
+    loop {
+        let value = get_input(prompt);
+        match value {
+            0 => return Orientation::Horizontal,
+            1 => return Orientation::Vertical,
+            _ => println!("Please enter 0 or 1"),
+        }
+    }
+}
+
+fn get_opponent(prompt: &str) -> Opponent {
+    loop {
+        let value = get_input(prompt);
+        match value {
+            0 => return Opponent::Computer,
+            1 => return Opponent::Host,
+            2 => return Opponent::Guest,
+            3 => return Opponent::Referee,
+            _ => println!("Please enter 0, 1, 2 or 3"),
+        }
+    }
+}
+
+// whole line of text, for things that aren't numbers like addresses
+fn get_text(prompt: &str) -> String {
+    println!("{}", prompt);
+    let mut input = String::new();
+    stdin().read_line(&mut input).unwrap();
+    input.trim().to_string()
+}
+
 fn get_bool(prompt: &str) -> bool {
     loop {
         let value = get_input(prompt);
@@ -1078,3 +6085,581 @@ fn get_bool(prompt: &str) -> bool {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::dev::{MockProver, VerifyFailure};
+    use std::io::Cursor;
+
+    // every circuit for the small board fits in K = 10
+    const K: u32 = 10;
+
+    // small board so the tests stay quick in debug builds
+    fn small_game() -> Prover {
+        let mut game = Prover::new(GridSize { width: 6, height: 5 }, &[3, 2], AdjacencyRule::Forbidden);
+        game.place_ship(0, 0, 3, Orientation::Horizontal).unwrap();
+        game.place_ship(5, 2, 2, Orientation::Vertical).unwrap();
+        game
+    }
+
+    fn ship(x: u8, y: u8, orientation: Orientation, length: u8) -> Ship {
+        Ship { x, y, orientation, length }
+    }
+
+    fn satisfied<C: Circuit<Fp>>(circuit: &C, public_inputs: Vec<Fp>) -> bool {
+        MockProver::run(K, circuit, vec![public_inputs]).unwrap().verify().is_ok()
+    }
+
+    // true when the only thing wrong is a value missing from a lookup table
+    fn fails_lookup<C: Circuit<Fp>>(circuit: &C, public_inputs: Vec<Fp>) -> bool {
+        let failures = MockProver::run(K, circuit, vec![public_inputs]).unwrap().verify().unwrap_err();
+        failures.iter().all(|failure| matches!(failure, VerifyFailure::Lookup { .. }))
+    }
+
+    fn shot_circuit(game: &Prover, x: u8, y: u8, hit: bool) -> (BattleshipCircuit, Vec<Fp>) {
+        let circuit = BattleshipCircuit {
+            ships: game.ship_witnesses(),
+            fleet: game.fleet.clone(),
+            attack_x: Value::known(Fp::from(x as u64)),
+            attack_y: Value::known(Fp::from(y as u64)),
+            hit: Value::known(Fp::from(hit as u64)),
+            grid_size: game.grid_size,
+        };
+        (circuit, shot_public_inputs(hit, x, y, &game.commitment_values()))
+    }
+
+    fn shot_satisfied(game: &Prover, x: u8, y: u8, hit: bool) -> bool {
+        let (circuit, public_inputs) = shot_circuit(game, x, y, hit);
+        satisfied(&circuit, public_inputs)
+    }
+
+    // setup circuit for ships on the small board, checked against the public fleet lengths
+    fn setup_circuit(ships: &[Ship], fleet: &[u8], adjacency: AdjacencyRule) -> (FleetSetupCircuit, Vec<Fp>) {
+        let circuit = FleetSetupCircuit {
+            ships: ships.iter().map(|ship| ShipWitness::known(ship, 3)).collect(),
+            fleet: fleet.to_vec(),
+            grid_size: GridSize { width: 6, height: 5 },
+            adjacency,
+        };
+        let commitments: Vec<Fp> = ships.iter().map(|ship| Prover::compute_commitment(ship, 3)).collect();
+        (circuit, setup_public_inputs(&commitments))
+    }
+
+    fn setup_satisfied(ships: &[Ship], adjacency: AdjacencyRule) -> bool {
+        let fleet: Vec<u8> = ships.iter().map(|ship| ship.length).collect();
+        let (circuit, public_inputs) = setup_circuit(ships, &fleet, adjacency);
+        satisfied(&circuit, public_inputs)
+    }
+
+    #[test]
+    fn shot_circuit_rejects_wrong_answers() {
+        let game = small_game();
+        assert!(shot_satisfied(&game, 1, 0, true));
+        assert!(shot_satisfied(&game, 3, 3, false));
+        // flipped hit bit
+        assert!(!shot_satisfied(&game, 1, 0, false));
+        assert!(!shot_satisfied(&game, 3, 3, true));
+    }
+
+    #[test]
+    fn shot_circuit_range_checks_the_attack() {
+        let game = small_game();
+        assert!(shot_satisfied(&game, 5, 4, false));
+        // attacks off the board fail the range lookup, even as a miss
+        for (x, y) in [(6, 0), (0, 5), (255, 255)] {
+            let (circuit, public_inputs) = shot_circuit(&game, x, y, false);
+            assert!(fails_lookup(&circuit, public_inputs), "({}, {})", x, y);
+        }
+    }
+
+    #[test]
+    fn setup_circuit_checks_ships_are_on_the_board() {
+        use Orientation::*;
+        let first = ship(0, 0, Horizontal, 3);
+        assert!(setup_satisfied(&[first, ship(5, 3, Vertical, 2)], AdjacencyRule::Allowed));
+
+        // ships running off the board fail the range lookup on either axis
+        for off_board in [ship(5, 0, Horizontal, 2), ship(5, 4, Vertical, 2)] {
+            let (circuit, public_inputs) = setup_circuit(&[first, off_board], &[3, 2], AdjacencyRule::Allowed);
+            assert!(fails_lookup(&circuit, public_inputs));
+        }
+
+        // the same ship can't be placed twice, and a ship has to be as long as the fleet says
+        assert!(!setup_satisfied(&[first, first], AdjacencyRule::Allowed));
+        let (circuit, public_inputs) = setup_circuit(&[first, ship(5, 3, Vertical, 1)], &[3, 2], AdjacencyRule::Allowed);
+        assert!(!satisfied(&circuit, public_inputs));
+    }
+
+    #[test]
+    fn setup_circuit_checks_overlap_and_adjacency() {
+        use Orientation::*;
+        let first = ship(0, 0, Horizontal, 3);
+        assert!(setup_satisfied(&[first, ship(5, 2, Vertical, 2)], AdjacencyRule::Forbidden));
+        // one empty cell between the ships is enough
+        assert!(setup_satisfied(&[first, ship(4, 0, Vertical, 2)], AdjacencyRule::Forbidden));
+
+        // crossing ships are never allowed
+        let crossing = [first, ship(1, 0, Vertical, 2)];
+        assert!(!setup_satisfied(&crossing, AdjacencyRule::Allowed));
+        assert!(!setup_satisfied(&crossing, AdjacencyRule::Forbidden));
+
+        // touching side by side, end to end or diagonally only passes when the rules allow it
+        for touching in [ship(0, 1, Horizontal, 2), ship(3, 0, Horizontal, 2), ship(3, 1, Horizontal, 2)] {
+            assert!(setup_satisfied(&[first, touching], AdjacencyRule::Allowed));
+            assert!(!setup_satisfied(&[first, touching], AdjacencyRule::Forbidden));
+        }
+    }
+
+    #[test]
+    fn merkle_shot_circuit_opens_the_attacked_cell() {
+        let mut game = small_game();
+        game.commit_board();
+        let board = game.board_commitment.clone().unwrap();
+        let depth = board_depth(game.grid_size);
+        // opens the cell at `index` while claiming the attack was at (x, y)
+        let opening = |index: usize, occupied: u8, x: u8, y: u8| MerkleShotCircuit {
+            occupied: Value::known(Fp::from(occupied as u64)),
+            salt: Value::known(Fp::from(board.salts[index])),
+            siblings: board.path(index).into_iter().map(Value::known).collect(),
+            index_bits: (0..depth).map(|level| Value::known(Fp::from(((index >> level) & 1) as u64))).collect(),
+            attack_x: Value::known(Fp::from(x as u64)),
+            attack_y: Value::known(Fp::from(y as u64)),
+            grid_size: game.grid_size,
+        };
+        let index = |x: u8, y: u8| game.grid_size.index(x as usize, y as usize);
+
+        assert!(satisfied(&opening(index(1, 0), 1, 1, 0), board_shot_public_inputs(true, 1, 0, board.root)));
+        assert!(satisfied(&opening(index(4, 4), 0, 4, 4), board_shot_public_inputs(false, 4, 4, board.root)));
+        // lying about the occupancy bit breaks the path to the root
+        assert!(!satisfied(&opening(index(1, 0), 0, 1, 0), board_shot_public_inputs(false, 1, 0, board.root)));
+        // opening a different (empty) cell than the one attacked
+        assert!(!satisfied(&opening(index(4, 4), 0, 1, 0), board_shot_public_inputs(false, 1, 0, board.root)));
+    }
+
+    #[test]
+    fn board_setup_circuit_counts_the_ship_cells() {
+        // 3x3 so the tree over every cell still fits in K = 10
+        let grid_size = GridSize { width: 3, height: 3 };
+        let board_setup = |occupied: Vec<bool>, fleet: &[u8]| {
+            let salts: Vec<u64> = (1..=occupied.len() as u64).collect();
+            let board = BoardCommitment::from_openings(grid_size, occupied.clone(), salts.clone());
+            let circuit = BoardSetupCircuit {
+                occupied: occupied.iter().map(|&occupied| Value::known(Fp::from(occupied as u64))).collect(),
+                salts: salts.iter().map(|&salt| Value::known(Fp::from(salt))).collect(),
+                grid_size,
+                ship_cells: fleet_cells(fleet),
+            };
+            (circuit, board.root)
+        };
+        let mut two_cells = vec![false; 9];
+        two_cells[0] = true;
+        two_cells[1] = true;
+
+        let (circuit, root) = board_setup(two_cells.clone(), &[2]);
+        assert!(satisfied(&circuit, board_setup_public_inputs(root)));
+        // a different root than the one the cells hash to
+        let (_, other_root) = board_setup(vec![false; 9], &[2]);
+        assert!(!satisfied(&circuit, board_setup_public_inputs(other_root)));
+
+        // an empty board, or one with an extra occupied cell, doesn't match the fleet
+        let (circuit, root) = board_setup(vec![false; 9], &[2]);
+        assert!(!satisfied(&circuit, board_setup_public_inputs(root)));
+        let (circuit, root) = board_setup(two_cells.clone(), &[3]);
+        assert!(!satisfied(&circuit, board_setup_public_inputs(root)));
+
+        // an occupancy value of 2 would make the count right with a single cell
+        let (mut circuit, _) = board_setup(two_cells, &[2]);
+        circuit.occupied = (0..9).map(|i| Value::known(Fp::from(if i == 0 { 2 } else { 0 }))).collect();
+        let leaves: Vec<Fp> = (0..9u64).map(|i| poseidon_pair(Fp::from(if i == 0 { 2 } else { 0 }), Fp::from(i + 1))).collect();
+        let mut level = leaves;
+        level.resize(16, poseidon_pair(Fp::ZERO, Fp::ZERO));
+        while level.len() > 1 {
+            level = level.chunks(2).map(|pair| poseidon_pair(pair[0], pair[1])).collect();
+        }
+        assert!(!satisfied(&circuit, board_setup_public_inputs(level[0])));
+    }
+
+    #[test]
+    fn sunk_circuit_needs_every_cell_hit() {
+        let game = small_game();
+        let max_hits = fleet_cells(&game.fleet);
+        let sunk = |index: usize, length: u8, hits: &[(u8, u8)]| {
+            let circuit = SunkShipCircuit {
+                ship: ShipWitness::known(&game.ships[index], game.ship_commitments[index].salt),
+                length,
+                max_hits,
+            };
+            let commitment = game.ship_commitments[index].commitment;
+            satisfied(&circuit, sunk_public_inputs(commitment, hits, max_hits, game.grid_size))
+        };
+
+        assert!(sunk(1, 2, &[(0, 0), (5, 3), (5, 2)]));
+        // one cell of the ship missing from the hit list
+        assert!(!sunk(1, 2, &[(5, 2), (0, 0)]));
+        // a ship that wasn't hit at all, and the right hits claimed for a longer ship type
+        assert!(!sunk(0, 3, &[(5, 2), (5, 3)]));
+        assert!(!sunk(1, 3, &[(5, 2), (5, 3)]));
+    }
+
+    #[test]
+    fn status_circuit_matches_the_hit_list() {
+        let mut game = small_game();
+        let max_hits = fleet_cells(&game.fleet);
+        let status = |game: &Prover, all_sunk: bool| {
+            let circuit = FleetStatusCircuit {
+                ships: game.ship_witnesses(),
+                fleet: game.fleet.clone(),
+                max_hits,
+                all_sunk: Value::known(Fp::from(all_sunk as u64)),
+            };
+            let public_inputs =
+                status_public_inputs(all_sunk, &game.commitment_values(), &game.hits_taken, max_hits, game.grid_size);
+            satisfied(&circuit, public_inputs)
+        };
+
+        // one ship cell short of game over
+        for (x, y) in [(5, 2), (5, 3), (0, 0), (1, 0)] {
+            game.record_hit(x, y);
+        }
+        assert!(status(&game, false));
+        assert!(!status(&game, true));
+
+        game.record_hit(2, 0);
+        assert!(status(&game, true));
+        assert!(!status(&game, false));
+    }
+
+    #[test]
+    fn link_circuit_needs_the_bridge_blindings() {
+        let game = small_game();
+        let bits = game.bits_for_grid_size();
+        let placement = &game.ship_range_proofs[1];
+        let public_inputs = link_public_inputs(game.ship_commitments[1].commitment, &placement.x.bridge, &placement.y.bridge);
+        let link = |(x, y): (Fq, Fq)| ShipLinkCircuit {
+            ship: game.ship_witnesses()[1],
+            blindings: [Value::known(x), Value::known(y)],
+            value_bits: bits,
+        };
+        let (x, y) = game.bridge_blindings[1];
+        assert!(satisfied(&link((x, y)), public_inputs.clone()));
+        assert!(!satisfied(&link((x + Fq::ONE, y)), public_inputs.clone()));
+        assert!(!satisfied(&link((x, y + Fq::ONE)), public_inputs));
+    }
+
+    #[test]
+    fn range_proofs_are_tight_at_the_board_edge() {
+        // one ship starts in the last column and one in the last row
+        let mut game = Prover::new(GridSize { width: 6, height: 5 }, &[2, 3], AdjacencyRule::Forbidden);
+        game.place_ship(5, 3, 2, Orientation::Vertical).unwrap();
+        game.place_ship(0, 4, 3, Orientation::Horizontal).unwrap();
+        let mut view = Verifier::new(game.publish());
+        assert!(view.verify_ship_proofs());
+
+        // checked against a board one narrower or one lower the coordinate equals the size,
+        // so size - 1 - v wraps around and the upper bound proof fails
+        for grid_size in [GridSize { width: 5, height: 5 }, GridSize { width: 6, height: 4 }] {
+            view.board.grid_size = grid_size;
+            assert!(!view.verify_ship_proofs(), "{}", grid_size);
+        }
+    }
+
+    #[test]
+    fn audit_catches_answers_the_reveal_contradicts() {
+        let game = small_game();
+        let mut view = Verifier::new(game.publish());
+        view.record_answer(1, 0, true);
+        view.record_answer(3, 3, false);
+        assert_eq!(view.audit(&game.reveal()), Vec::<String>::new());
+
+        // (5, 3) is the second ship's tail, a miss there was a lie
+        view.record_answer(5, 3, false);
+        let problems = view.audit(&game.reveal());
+        assert_eq!(problems.len(), 1, "{:?}", problems);
+        assert!(problems[0].contains("(5, 3)"), "{:?}", problems);
+
+        // a reveal with a moved ship doesn't open its commitment any more
+        let mut moved = game.reveal();
+        moved.ships[1].0.y = 1;
+        assert!(view.audit(&moved).iter().any(|problem| problem.contains("doesn't open")));
+    }
+
+    #[test]
+    fn published_board_round_trip() {
+        let game = small_game();
+        let bytes = game.publish().to_bytes();
+        let decoded = PublishedBoard::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.ship_commitments, game.commitment_values());
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert!(Verifier::new(decoded).verify_ship_proofs());
+    }
+
+    #[test]
+    fn published_board_rejects_bad_input() {
+        let bytes = small_game().publish().to_bytes();
+        for len in [0, 1, 2, 10, bytes.len() / 2, bytes.len() - 1] {
+            assert!(matches!(PublishedBoard::from_bytes(&bytes[..len]), Err(WireError::Truncated { .. })), "{} bytes", len);
+        }
+
+        let mut extra = bytes.clone();
+        extra.push(0);
+        assert_eq!(PublishedBoard::from_bytes(&extra).err(), Some(WireError::TrailingBytes(1)));
+
+        let mut newer = bytes.clone();
+        newer[0] = WIRE_VERSION + 1;
+        assert_eq!(PublishedBoard::from_bytes(&newer).err(), Some(WireError::UnsupportedVersion(WIRE_VERSION + 1)));
+
+        assert!(matches!(ShotProof::from_bytes(&bytes), Err(WireError::WrongKind { .. })));
+
+        // header, width, height, fleet blob, adjacency, the commitment count and the first commitment's
+        // length prefix and header come before the first commitment
+        let first_commitment = 2 + 4 + 4 + (4 + 2) + 1 + 4 + (4 + 2);
+        let mut non_canonical = bytes.clone();
+        non_canonical[first_commitment..first_commitment + 32].fill(0xff);
+        assert!(matches!(PublishedBoard::from_bytes(&non_canonical), Err(WireError::Malformed(_))));
+
+        // a commitment count bigger than the message can't make the reader allocate for it
+        let mut huge = bytes.clone();
+        huge[first_commitment - 10..first_commitment - 6].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(PublishedBoard::from_bytes(&huge), Err(WireError::Truncated { .. })));
+    }
+
+    // every message has to come back the same and every cut off version of it has to be refused as truncated
+    fn check_encoding<T>(bytes: &[u8], decode: impl Fn(&[u8]) -> Result<T, WireError>, encode: impl Fn(&T) -> Vec<u8>) {
+        assert_eq!(encode(&decode(bytes).unwrap()), bytes);
+        for len in 0..bytes.len() {
+            assert!(matches!(decode(&bytes[..len]), Err(WireError::Truncated { .. })), "{} of {} bytes", len, bytes.len());
+        }
+    }
+
+    fn wire_shot(hit: bool) -> ShotProof {
+        ShotProof {
+            attack_x: 4,
+            attack_y: 2,
+            hit,
+            proof: vec![7; 40],
+            public_inputs: vec![Fp::from(hit as u64), Fp::from(4), Fp::from(2), Fp::from(99)],
+        }
+    }
+
+    #[test]
+    fn ship_commitment_round_trip() {
+        let game = small_game();
+        let commitment = PublishedCommitment(game.ship_commitments[0].commitment);
+        check_encoding(&commitment.to_bytes(), PublishedCommitment::from_bytes, |c| c.to_bytes());
+        assert!(matches!(PublishedCommitment::from_bytes(&Attack { x: 1, y: 1 }.to_bytes()), Err(WireError::WrongKind { .. })));
+    }
+
+    #[test]
+    fn shot_messages_round_trip() {
+        let shot = wire_shot(true);
+        check_encoding(&shot.to_bytes(), ShotProof::from_bytes, ShotProof::to_bytes);
+        assert_eq!(ShotProof::from_bytes(&shot.to_bytes()).unwrap(), shot);
+
+        // a header that says miss over public inputs that say hit
+        let mut lying = shot.to_bytes();
+        lying[4] = 0;
+        assert!(matches!(ShotProof::from_bytes(&lying), Err(WireError::Malformed(_))));
+
+        let attack = Attack { x: 9, y: 3 };
+        check_encoding(&attack.to_bytes(), Attack::from_bytes, |a| a.to_bytes());
+        assert_eq!(Attack::from_bytes(&attack.to_bytes()).unwrap(), attack);
+
+        let answers = [
+            ShotAnswer { shot: wire_shot(false), sunk: vec![], fleet_status: None },
+            ShotAnswer {
+                shot: wire_shot(true),
+                sunk: vec![(1, vec![5; 30])],
+                fleet_status: Some((true, vec![6; 20])),
+            },
+        ];
+        for answer in answers {
+            check_encoding(&answer.to_bytes(), ShotAnswer::from_bytes, ShotAnswer::to_bytes);
+            let decoded = ShotAnswer::from_bytes(&answer.to_bytes()).unwrap();
+            assert_eq!(decoded.shot, answer.shot);
+            assert_eq!(decoded.sunk, answer.sunk);
+            assert_eq!(decoded.fleet_status, answer.fleet_status);
+        }
+    }
+
+    #[test]
+    fn board_reveal_round_trip() {
+        let ships = vec![(ship(0, 0, Orientation::Horizontal, 3), 11), (ship(5, 2, Orientation::Vertical, 2), u64::MAX)];
+        let per_ship = BoardReveal { ships: ships.clone(), cells: None };
+        check_encoding(&per_ship.to_bytes(), BoardReveal::from_bytes, BoardReveal::to_bytes);
+
+        let board = BoardReveal { ships, cells: Some((vec![true, false, true], vec![1, 2, 3])) };
+        check_encoding(&board.to_bytes(), BoardReveal::from_bytes, BoardReveal::to_bytes);
+        assert_eq!(BoardReveal::from_bytes(&board.to_bytes()).unwrap().cells, board.cells);
+
+        // orientation byte of the first ship
+        let mut bad = per_ship.to_bytes();
+        bad[2 + 4 + 2] = 2;
+        assert!(matches!(BoardReveal::from_bytes(&bad), Err(WireError::Malformed(_))));
+    }
+
+    #[test]
+    fn frames() {
+        let mut stream = Vec::new();
+        send_frame(&mut stream, b"first").unwrap();
+        send_frame(&mut stream, b"").unwrap();
+        let mut reader = Cursor::new(stream.clone());
+        assert_eq!(receive_frame(&mut reader).unwrap(), b"first");
+        assert_eq!(receive_frame(&mut reader).unwrap(), b"");
+        assert!(receive_frame(&mut reader).is_err());
+
+        // cut off in the middle of a frame
+        assert!(receive_frame(&mut Cursor::new(&stream[..7])).is_err());
+
+        // a length over the limit is refused before anything gets allocated for it
+        let oversize = ((MAX_FRAME_SIZE + 1) as u32).to_le_bytes();
+        let error = receive_frame(&mut Cursor::new(oversize)).unwrap_err();
+        assert!(error.contains("refusing"), "{}", error);
+    }
+
+    #[test]
+    fn k_follows_the_rules() {
+        let mut rules = GameRules {
+            grid_size: GridSize { width: 10, height: 10 },
+            fleet: vec![5, 4, 3, 3, 2],
+            adjacency: AdjacencyRule::Forbidden,
+            commitment_mode: CommitmentMode::PerShip,
+        };
+        assert_eq!(required_k(&rules), 10);
+
+        // the biggest fleet the rules allow
+        rules.grid_size = GridSize { width: 16, height: 16 };
+        rules.fleet = vec![(MAX_FLEET_CELLS / MAX_SHIPS) as u8; MAX_SHIPS];
+        assert!(rules.check().is_ok());
+        assert_eq!(required_k(&rules), 13);
+        rules.fleet.push(1);
+        assert!(rules.check().is_err());
+
+        // the biggest board board mode allows
+        rules.grid_size = GridSize { width: 12, height: 12 };
+        rules.fleet = vec![5, 4, 3, 3, 2];
+        rules.commitment_mode = CommitmentMode::Board;
+        assert!(rules.check().is_ok());
+        assert_eq!(required_k(&rules), 14);
+    }
+
+    fn parse_args(args: &str) -> Result<GameConfig, String> {
+        GameConfig::from_args(&args.split_whitespace().map(str::to_string).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn config_from_args() {
+        let file = std::env::temp_dir().join(format!("zk-battleship-test-{}-config.json", std::process::id()));
+        let path = file.to_str().unwrap();
+        std::fs::write(path, r#"{"width": 8, "height": 6, "fleet": [4, 3], "adjacency": "allowed"}"#).unwrap();
+
+        let config = parse_args(&format!("--config {}", path)).unwrap();
+        assert_eq!((config.width(), config.height(), config.fleet()), (8, 6, vec![4, 3]));
+        assert_eq!(config.adjacency, Some(AdjacencyRule::Allowed));
+        assert_eq!(config.commitment_mode, None);
+
+        // flags after the file win, flags before it lose
+        let config = parse_args(&format!("--fleet 2 --config {} --height 9 --adjacency forbidden", path)).unwrap();
+        assert_eq!((config.width(), config.height(), config.fleet()), (8, 9, vec![4, 3]));
+        assert_eq!(config.adjacency, Some(AdjacencyRule::Forbidden));
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(parse_args("").unwrap(), GameConfig::default());
+        for bad in [
+            "--size 8",
+            "--grid",
+            "--grid 0",
+            "--width 300",
+            "--grid eight",
+            "--adjacency sometimes",
+            "--fleet 3,0,2",
+            "--fleet 3,,2",
+            "--fleet 11",
+            "--grid 3 --fleet 3,3,3,1",
+            "--fleet 1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1",
+            "--grid 16 --fleet 5,5,5,5,5,5,5,5,5,5,5,5,5,5",
+            "--grid 13 --commitment board",
+        ] {
+            assert!(parse_args(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn params_header_errors() {
+        let path = |name: &str| {
+            let file = std::env::temp_dir().join(format!("zk-battleship-test-{}-{}.bin", std::process::id(), name));
+            file.to_str().unwrap().to_string()
+        };
+        let good = path("params");
+        write_params(&good, 4);
+        let bytes = std::fs::read(&good).unwrap();
+        assert!(read_params(&good, 4).is_ok());
+        // the circuits need more rows than these params have
+        assert_eq!(read_params(&good, 5).err(), Some(ParamsError::WrongK { stored: 4, required: 5 }));
+        std::fs::remove_file(&good).unwrap();
+        assert_eq!(read_params(&good, 4).err(), Some(ParamsError::Missing));
+
+        let broken = path("broken");
+        let read_changed = |change: fn(&mut Vec<u8>)| {
+            let mut changed = bytes.clone();
+            change(&mut changed);
+            std::fs::write(&broken, changed).unwrap();
+            read_params(&broken, 4).err()
+        };
+        assert_eq!(read_changed(|bytes| bytes[0] ^= 1), Some(ParamsError::NotAParamsFile));
+        assert_eq!(
+            read_changed(|bytes| bytes[PARAMS_MAGIC.len()] = PARAMS_VERSION + 1),
+            Some(ParamsError::UnsupportedVersion(PARAMS_VERSION + 1))
+        );
+        assert!(matches!(read_changed(|bytes| *bytes.last_mut().unwrap() ^= 1), Some(ParamsError::Corrupt(_))));
+        assert!(matches!(read_changed(|bytes| bytes[PARAMS_HEADER_SIZE - 1] ^= 1), Some(ParamsError::Corrupt(_))));
+        std::fs::remove_file(&broken).unwrap();
+    }
+
+    // smallest match there is, one ship each on a 2x2 board, played with real proofs
+    #[test]
+    fn transcript_round_trip_and_tampering() {
+        let rules = GameRules {
+            grid_size: GridSize { width: 2, height: 2 },
+            fleet: vec![1],
+            adjacency: AdjacencyRule::Allowed,
+            commitment_mode: CommitmentMode::PerShip,
+        };
+        let params: Params<EqAffine> = Params::new(required_k(&rules));
+        let keys = initialize_game_keys(&params, &rules);
+        let mut games = [(0, 0), (1, 1)].map(|(x, y)| {
+            let mut game = Prover::new(rules.grid_size, &rules.fleet, rules.adjacency);
+            game.place_ship(x, y, 1, Orientation::Horizontal).unwrap();
+            game
+        });
+
+        let verifying_keys = keys.verifying();
+        let mut game_match = Match::new(&params, &verifying_keys, rules.clone(), ["alice", "bob"], Side::Player);
+        for (side, game) in [Side::Player, Side::Opponent].into_iter().zip(&games) {
+            game_match.commit(side, game.publish()).unwrap();
+        }
+        for (side, game) in [Side::Player, Side::Opponent].into_iter().zip(&games) {
+            let (setup_proof, link_proofs) = prove_setup(game, &params, &keys);
+            game_match.setup(side, setup_proof, link_proofs).unwrap();
+        }
+        // alice misses, bob misses, alice sinks bob's only ship
+        for (attacker, x, y) in [(Side::Player, 0, 1), (Side::Opponent, 1, 0), (Side::Player, 1, 1)] {
+            let defender = attacker.other();
+            game_match.attack(attacker, Attack { x, y }).unwrap();
+            answer_locally(&mut game_match, &mut games[defender.index()], &keys, defender, Attack { x, y }).unwrap();
+        }
+        assert_eq!(game_match.winner(), Some(Side::Player));
+
+        let path = std::env::temp_dir().join(format!("zk-battleship-test-{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        game_match.transcript.save(path).unwrap();
+        let mut transcript = GameTranscript::load(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(verify_transcript(&transcript, &params), Vec::<String>::new());
+        assert_eq!(transcript.winner.as_deref(), Some("alice"));
+
+        // alice's answer to bob's shot turned into a hit, its proof says miss
+        transcript.shots[1].result = shot_result(true);
+        assert!(!verify_transcript(&transcript, &params).is_empty());
+    }
+}